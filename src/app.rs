@@ -2,8 +2,10 @@ use std::thread;
 use std::time::Duration;
 
 use crate::api;
+use crate::rating::{self, Rating};
 use crate::solver;
-use crate::sudoku::{Difficulty, Sudoku};
+use crate::strategies::{Pipeline, Step};
+use crate::sudoku::{Difficulty, Sudoku, Uniqueness};
 use crate::ui;
 
 use iced::Theme;
@@ -13,15 +15,40 @@ use iced::stream::try_channel;
 use iced::widget::progress_bar;
 use iced::widget::text::Span;
 use iced::widget::toggler;
-use iced::widget::{Column, Space, Tooltip, button, column, container, row, text, tooltip};
+use iced::widget::{
+    Column, Space, Tooltip, button, column, container, row, scrollable, text, tooltip,
+};
 use iced::{Subscription, Task, window};
 
 pub struct App {
     sudoku: Sudoku,
     selected_cell: Option<(usize, usize)>,
+    /// Every strategy of the pipeline by name and whether the solver may use it
+    strategies: Vec<(String, bool)>,
+    /// Explanation of the last step the strategies applied
+    last_step: Option<String>,
+    /// Step a hint is being revealed for and how much of it is shown
+    hint: Option<(Step, HintLevel)>,
+    /// Result of checking a board entered by hand, shown above the board
+    warning: Option<String>,
     // Internals
     window_size: (f32, f32),
     view_solution: bool,
+    /// Draw the pencil marks of every empty cell
+    show_candidates: bool,
+    /// Also flag entries that break no rule but differ from the solution
+    check_solution: bool,
+}
+
+/// How much of a hint has been revealed. Asking after the technique applies the step.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum HintLevel {
+    /// Highlight the rows, columns or boxes to look at
+    Region,
+    /// Name the technique that makes progress there
+    Technique,
+    /// The step was applied, its cells are highlighted
+    Applied,
 }
 
 #[derive(Debug, Clone)]
@@ -30,17 +57,47 @@ pub enum Message {
     SelectedCell(usize, usize),
     SelectedNumber(usize),
     SelectedDifficulty(Difficulty),
+    FetchedBoard(Box<Sudoku>),
     SetBoard([[usize; 9]; 9]),
     ClearBoard,
     ResetBoard,
+    CheckBoard,
+    RatedBoard([[usize; 9]; 9], Rating),
+    LockBoard,
     // Solvers
     BruteForce,
+    Solve,
+    ToggleStrategy(usize, bool),
+    ApplyStep,
+    ApplyAll,
+    Hint,
     // Internals
     WindowResize(f32, f32),
     ViewSolution(bool),
+    ShowCandidates(bool),
+    CheckSolution(bool),
     Error,
 }
 
+/// Fetches a board of the given API grade and rates it, away from the UI thread. Each grade
+/// spans several tiers, so the board is labelled with the one it rates as.
+async fn fetch_board(difficulty: String) -> Box<Sudoku> {
+    let (puzzle, solution) = api::get_board(difficulty);
+    let mut sudoku = Sudoku::new();
+    sudoku.set_board(puzzle);
+    sudoku.set_puzzle(puzzle);
+    sudoku.set_solution(solution);
+    let rating = rating::rate(&sudoku);
+    sudoku.set_difficulty(Some(rating.difficulty()));
+    sudoku.set_rating(Some(rating.hardest));
+    Box::new(sudoku)
+}
+
+/// Rates a board entered by hand, away from the UI thread
+async fn rate_board(sudoku: Sudoku) -> ([[usize; 9]; 9], Rating) {
+    (sudoku.get_board(), rating::rate(&sudoku))
+}
+
 impl Default for App {
     fn default() -> Self {
         Self::new()
@@ -52,13 +109,35 @@ impl App {
         Self {
             sudoku: Sudoku::new(),
             selected_cell: None,
+            strategies: Pipeline::default()
+                .strategies()
+                .iter()
+                .map(|strategy| (strategy.name().to_string(), true))
+                .collect(),
+            last_step: None,
+            hint: None,
+            warning: None,
             // Internals
             window_size: (600.0, 600.0),
             view_solution: false,
+            show_candidates: false,
+            check_solution: false,
         }
     }
 
     pub fn update(&mut self, message: Message) -> Task<Message> {
+        // A hint only holds for the board it was found on
+        if !matches!(
+            message,
+            Message::Hint
+                | Message::SelectedCell(..)
+                | Message::WindowResize(..)
+                | Message::ViewSolution(_)
+                | Message::ShowCandidates(_)
+                | Message::CheckSolution(_)
+        ) {
+            self.hint = None;
+        }
         match message {
             Message::SelectedCell(row, col) => {
                 self.selected_cell = Some((row, col));
@@ -68,21 +147,25 @@ impl App {
                 if let Some((row, col)) = self.selected_cell {
                     self.sudoku.update_cell(row, col, num);
                     self.selected_cell = None;
+                    self.check_entry();
                 }
                 Task::none()
             }
             Message::SelectedDifficulty(diff) => {
+                // The API only grades its boards easy, medium or hard
                 let api_diff = match diff {
-                    Difficulty::Easy => "easy",
+                    Difficulty::Beginner | Difficulty::Easy => "easy",
                     Difficulty::Medium => "medium",
-                    Difficulty::Hard => "hard",
+                    Difficulty::Hard
+                    | Difficulty::Expert
+                    | Difficulty::Diabolical
+                    | Difficulty::Extreme => "hard",
                 };
-                let (puzzle, solution) = api::get_board(api_diff.to_string());
-                //println!("got {:?}", puzzle);
-                self.sudoku.set_board(puzzle);
-                self.sudoku.set_puzzle(puzzle);
-                self.sudoku.set_solution(solution);
-                self.sudoku.set_difficulty(Some(diff));
+                Task::perform(fetch_board(api_diff.to_string()), Message::FetchedBoard)
+            }
+            Message::FetchedBoard(sudoku) => {
+                self.sudoku = *sudoku;
+                self.warning = None;
                 Task::none()
             }
             Message::SetBoard(board) => {
@@ -91,12 +174,40 @@ impl App {
             }
             Message::ClearBoard => {
                 self.sudoku.clear_board();
+                self.warning = None;
                 Task::none()
             }
             Message::ResetBoard => {
                 self.sudoku.reset();
                 Task::none()
             }
+            Message::CheckBoard => self.check_board(),
+            Message::RatedBoard(board, rating) => {
+                // The board may have been edited or locked while it was being rated
+                if self.sudoku.get_puzzle().is_none() && self.sudoku.get_board() == board {
+                    self.sudoku.set_difficulty(Some(rating.difficulty()));
+                    self.sudoku.set_rating(Some(rating.hardest));
+                }
+                Task::none()
+            }
+            Message::LockBoard => {
+                if self.sudoku.get_puzzle().is_none() {
+                    let board = self.sudoku.get_board();
+                    match solver::solve(board).filter(|_| self.sudoku.has_unique_solution()) {
+                        Some(solution) => {
+                            self.sudoku.set_puzzle(board);
+                            self.sudoku.set_solution(solution);
+                            self.warning =
+                                Some("The entered digits are now the givens".to_string());
+                        }
+                        None => {
+                            self.warning =
+                                Some("Check that the board has one solution first".to_string());
+                        }
+                    }
+                }
+                Task::none()
+            }
             // Solvers
             Message::BruteForce => {
                 let puzzle = match self.sudoku.get_puzzle() {
@@ -111,6 +222,52 @@ impl App {
                     },
                 )
             }
+            Message::Solve => {
+                match solver::solve(self.sudoku.get_board()) {
+                    Some(board) => self.sudoku.set_board(board),
+                    None => self.last_step = Some("The board has no solution".to_string()),
+                }
+                Task::none()
+            }
+            Message::ToggleStrategy(index, enabled) => {
+                if let Some(strategy) = self.strategies.get_mut(index) {
+                    strategy.1 = enabled;
+                }
+                Task::none()
+            }
+            Message::ApplyStep => {
+                self.last_step = match self.pipeline().next_step(&self.sudoku) {
+                    Some(step) => {
+                        step.apply(&mut self.sudoku);
+                        Some(step.explanation)
+                    }
+                    None => Some("No enabled strategy applies".to_string()),
+                };
+                Task::none()
+            }
+            Message::Hint => {
+                self.hint = match self.hint.take() {
+                    None | Some((_, HintLevel::Applied)) => {
+                        let step = self.pipeline().next_step(&self.sudoku);
+                        if step.is_none() {
+                            self.last_step = Some("No hint available".to_string());
+                        }
+                        step.map(|step| (step, HintLevel::Region))
+                    }
+                    Some((step, HintLevel::Region)) => Some((step, HintLevel::Technique)),
+                    Some((step, HintLevel::Technique)) => {
+                        step.apply(&mut self.sudoku);
+                        self.last_step = Some(step.explanation.clone());
+                        Some((step, HintLevel::Applied))
+                    }
+                };
+                Task::none()
+            }
+            Message::ApplyAll => {
+                let steps = self.pipeline().solve(&mut self.sudoku);
+                self.last_step = Some(format!("Applied {} steps", steps.len()));
+                Task::none()
+            }
             // Internals
             Message::WindowResize(width, height) => {
                 self.window_size = (width, height);
@@ -120,6 +277,14 @@ impl App {
                 self.view_solution = value;
                 Task::none()
             }
+            Message::ShowCandidates(value) => {
+                self.show_candidates = value;
+                Task::none()
+            }
+            Message::CheckSolution(value) => {
+                self.check_solution = value;
+                Task::none()
+            }
             Message::Error => {
                 println!("Error");
                 Task::none()
@@ -127,27 +292,82 @@ impl App {
             _ => Task::none(),
         }
     }
+    /// Warns as soon as a board being entered by hand can no longer be solved. Several
+    /// solutions are expected until every given is in, so that waits for `check_board`.
+    fn check_entry(&mut self) {
+        if self.sudoku.get_puzzle().is_some() {
+            return;
+        }
+        // Any earlier check no longer holds for the edited board
+        self.sudoku.set_unique_solution(false);
+        self.sudoku.set_difficulty(None);
+        self.sudoku.set_rating(None);
+        self.warning =
+            (self.sudoku.count_solutions(1) == 0).then(|| "This board has no solution".to_string());
+    }
+    /// Reports how many solutions a board entered by hand has and rates it once it makes a
+    /// proper puzzle. Uniqueness based strategies are only trusted once it has exactly one
+    /// solution.
+    fn check_board(&mut self) -> Task<Message> {
+        if self.sudoku.get_puzzle().is_some() {
+            return Task::none();
+        }
+        let uniqueness = self.sudoku.uniqueness();
+        self.sudoku
+            .set_unique_solution(uniqueness == Uniqueness::Unique);
+        self.warning = Some(
+            match uniqueness {
+                Uniqueness::NoSolution => "This board has no solution",
+                Uniqueness::Unique => "This board has one solution",
+                Uniqueness::Multiple => "This board has multiple solutions",
+            }
+            .to_string(),
+        );
+        // Only a proper puzzle can be graded
+        if uniqueness == Uniqueness::Unique {
+            Task::perform(rate_board(self.sudoku.clone()), |(board, rating)| {
+                Message::RatedBoard(board, rating)
+            })
+        } else {
+            Task::none()
+        }
+    }
+    /// The strategy pipeline restricted to the enabled strategies
+    fn pipeline(&self) -> Pipeline {
+        Pipeline::default().filter(|strategy| {
+            self.strategies
+                .iter()
+                .any(|(name, enabled)| *enabled && name == strategy.name())
+        })
+    }
     pub fn view(&self) -> Column<'_, Message> {
         let (width, height) = self.window_size;
 
         let mut difficulty_row = row![];
-        for (diff, caller) in [
-            ("Easy", Difficulty::Easy),
-            ("Medium", Difficulty::Medium),
-            ("Hard", Difficulty::Hard),
-        ] {
-            let btn = button(diff).on_press(Message::SelectedDifficulty(caller));
+        for diff in Difficulty::all() {
+            let btn = button(text(diff.to_string())).on_press(Message::SelectedDifficulty(diff));
             difficulty_row = difficulty_row.push(btn);
         }
 
+        let mut strategy_list = column![].spacing(5);
+        for (index, (name, enabled)) in self.strategies.iter().enumerate() {
+            strategy_list = strategy_list.push(ui::strategy_ui(index, name, *enabled));
+        }
+
         let solver_bar = column![
             "Solvers",
             button("Brute Force").on_press(Message::BruteForce),
+            button("Solve").on_press(Message::Solve),
+            button("Apply next step").on_press(Message::ApplyStep),
+            button("Apply all").on_press(Message::ApplyAll),
+            button("Hint").on_press(Message::Hint),
+            text(match &self.hint {
+                Some((_, HintLevel::Region)) => "Look at the highlighted cells".to_string(),
+                Some((step, HintLevel::Technique)) => format!("Try a {}", step.technique.name()),
+                Some((_, HintLevel::Applied)) | None => self.last_step.clone().unwrap_or_default(),
+            }),
             "Strategies",
-            ui::strategy_ui(),
-            ui::strategy_ui(),
-            ui::strategy_ui(),
-            ui::strategy_ui(),
+            scrollable(strategy_list),
         ]
         .spacing(10)
         .padding(5)
@@ -159,9 +379,17 @@ impl App {
             difficulty_row.spacing(5).wrap(),
             button("Clear").on_press(Message::ClearBoard),
             button("Reset").on_press(Message::ResetBoard),
+            button("Check board").on_press(Message::CheckBoard),
+            button("Lock givens").on_press(Message::LockBoard),
             toggler(self.view_solution)
                 .label("View Solution")
                 .on_toggle(Message::ViewSolution),
+            toggler(self.show_candidates)
+                .label("Show Candidates")
+                .on_toggle(Message::ShowCandidates),
+            toggler(self.check_solution)
+                .label("Check against solution")
+                .on_toggle(Message::CheckSolution),
             if self.view_solution {
                 ui::solution_ui(self.sudoku.get_solution(), width * 0.2 / 9.0 - 2.0)
             } else {
@@ -173,24 +401,26 @@ impl App {
         .width(width * 0.2)
         .align_x(iced::Center);
 
-        let lbldiff = match self.sudoku.get_difficulty() {
-            Some(diff) if diff == Difficulty::Easy => "Easy",
-            Some(diff) if diff == Difficulty::Medium => "Medium",
-            Some(diff) if diff == Difficulty::Hard => "Hard",
-            _ => "Select a difficulty to play",
+        let lbldiff = match (self.sudoku.get_difficulty(), self.sudoku.get_rating()) {
+            (Some(diff), Some(rating)) => format!("{} ({:.1})", diff, rating),
+            (Some(diff), None) => diff.to_string(),
+            _ => "Select a difficulty to play".to_string(),
         };
 
         let stopwatch_ui = "00:00";
 
-        let board_headers = column![row![
-            "Difficulty:",
-            Space::with_width(20),
-            lbldiff,
-            Space::with_width(50),
-            "Time Spent:",
-            Space::with_width(20),
-            stopwatch_ui
-        ]];
+        let board_headers = column![
+            row![
+                "Difficulty:",
+                Space::with_width(20),
+                text(lbldiff),
+                Space::with_width(50),
+                "Time Spent:",
+                Space::with_width(20),
+                stopwatch_ui
+            ],
+            text(self.warning.clone().unwrap_or_default()),
+        ];
 
         let board = self.sudoku.get_board();
         let puzzle = self.sudoku.get_puzzle();
@@ -203,6 +433,23 @@ impl App {
             println!("WON");
         }
 
+        // The exact cells would give the step away, so they only show once it is applied
+        let hinted: Vec<(usize, usize)> = match &self.hint {
+            Some((step, HintLevel::Applied)) => {
+                step.highlights.iter().map(|&(cell, _)| cell).collect()
+            }
+            Some((step, _)) => step
+                .region()
+                .iter()
+                .flat_map(|house| house.cells())
+                .collect(),
+            None => vec![],
+        };
+        let mut errors = self.sudoku.conflicts();
+        if self.check_solution {
+            errors.extend(self.sudoku.wrong_entries());
+        }
+
         let mut ui_col = column![];
         for row in 0..9 {
             let mut ui_row = row![];
@@ -226,17 +473,23 @@ impl App {
                     ui_num_col.into()
                 } else {
                     let num = board[row][col];
-                    let mut btn = button(
-                        text(if num == 0 {
-                            "".to_string()
-                        } else {
-                            num.to_string()
-                        })
-                        .size(cell_size * 0.5)
-                        .center(),
-                    )
-                    .height(cell_size)
-                    .width(cell_size);
+                    let label = if num != 0 {
+                        text(num.to_string()).size(cell_size * 0.5)
+                    } else if self.show_candidates {
+                        text(ui::candidates_text(
+                            self.sudoku.get_cell_candidates(row, col),
+                        ))
+                        .size(cell_size * 0.18)
+                    } else {
+                        text("")
+                    };
+                    let mut btn = button(label.center()).height(cell_size).width(cell_size);
+                    if hinted.contains(&(row, col)) {
+                        btn = btn.style(button::success);
+                    }
+                    if errors.contains(&(row, col)) {
+                        btn = btn.style(button::danger);
+                    }
                     if let Some(puz) = puzzle {
                         if puz[row][col] == 0 {
                             btn = btn.on_press(Message::SelectedCell(row, col))
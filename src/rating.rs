@@ -0,0 +1,189 @@
+use crate::strategies::{Pipeline, Technique};
+use crate::sudoku::{Difficulty, Sudoku};
+
+/// Rating given to a puzzle the strategies cannot finish
+pub const UNSOLVED_RATING: f32 = 10.0;
+
+/// How hard a puzzle is for a human solver
+#[derive(Debug, Clone, PartialEq)]
+pub struct Rating {
+    /// Sudoku Explainer style rating of the hardest step
+    pub hardest: f32,
+    /// Technique of the hardest step, `None` when no step was needed
+    pub hardest_technique: Option<Technique>,
+    /// HoDoKu style score, the summed difficulty of every step
+    pub score: u32,
+    /// Whether the strategies solved the whole puzzle
+    pub solved: bool,
+}
+
+impl Rating {
+    /// Grade of the puzzle going by its hardest step
+    pub fn difficulty(&self) -> Difficulty {
+        match self.hardest {
+            h if h < 2.0 => Difficulty::Beginner,
+            h if h < 2.6 => Difficulty::Easy,
+            h if h < 4.0 => Difficulty::Medium,
+            h if h < 5.0 => Difficulty::Hard,
+            h if h < 6.5 => Difficulty::Expert,
+            h if h < 8.0 => Difficulty::Diabolical,
+            _ => Difficulty::Extreme,
+        }
+    }
+}
+
+/// Solves a copy of the board with every strategy, starting from fresh candidates, and rates
+/// the steps it took. Strategies are tried easiest first on the Sudoku Explainer scale, so
+/// the hardest step is one a solver actually needs.
+pub fn rate(sudoku: &Sudoku) -> Rating {
+    let mut sudoku = sudoku.clone();
+    sudoku.compute_candidates();
+    let pipeline = Pipeline::default().sorted_by(|strategy| se_rating(strategy.technique()));
+    let steps = pipeline.solve_weighted(&mut sudoku);
+    let solved = sudoku.get_board().iter().flatten().all(|&num| num != 0);
+    let hardest = steps
+        .iter()
+        .map(|(_, step)| step.technique)
+        .max_by(|a, b| se_rating(*a).total_cmp(&se_rating(*b)));
+    Rating {
+        hardest: match (solved, hardest) {
+            (false, _) => UNSOLVED_RATING,
+            (true, Some(technique)) => se_rating(technique),
+            (true, None) => 0.0,
+        },
+        hardest_technique: hardest,
+        score: steps.iter().map(|(weight, _)| weight).sum(),
+        solved,
+    }
+}
+
+/// Sudoku Explainer rating of a technique. Techniques Sudoku Explainer does not know are
+/// placed next to the ones they are closest to.
+pub fn se_rating(technique: Technique) -> f32 {
+    match technique {
+        Technique::HiddenSingle => 1.5,
+        Technique::NakedSingle => 2.3,
+        Technique::PointingPair | Technique::PointingTriple => 2.6,
+        Technique::BoxLineReduction => 2.8,
+        Technique::NakedPair => 3.0,
+        Technique::XWing => 3.2,
+        Technique::HiddenPair => 3.4,
+        Technique::FinnedXWing => 3.4,
+        Technique::SashimiXWing => 3.5,
+        Technique::NakedTriple => 3.6,
+        Technique::Swordfish => 3.8,
+        Technique::HiddenTriple => 4.0,
+        Technique::Skyscraper => 4.0,
+        Technique::FinnedSwordfish => 4.0,
+        Technique::TwoStringKite | Technique::EmptyRectangle => 4.1,
+        Technique::SashimiSwordfish => 4.1,
+        Technique::XYWing => 4.2,
+        Technique::XYZWing | Technique::WWing => 4.4,
+        Technique::UniqueRectangle1 => 4.5,
+        Technique::UniqueRectangle2
+        | Technique::UniqueRectangle3
+        | Technique::UniqueRectangle4
+        | Technique::UniqueRectangle5
+        | Technique::UniqueRectangle6
+        | Technique::HiddenRectangle => 4.6,
+        Technique::NakedQuad => 5.0,
+        Technique::SueDeCoq => 5.0,
+        Technique::Jellyfish => 5.2,
+        Technique::HiddenQuad => 5.4,
+        Technique::FinnedJellyfish => 5.4,
+        Technique::SashimiJellyfish => 5.5,
+        Technique::BugPlusOne => 5.6,
+        Technique::SimpleColoring => 5.7,
+        Technique::MultiColoring => 5.8,
+        Technique::XCycle => 6.5,
+        Technique::XYChain => 6.6,
+        Technique::Aic => 7.0,
+        Technique::GroupedAic => 7.2,
+        Technique::AlsXz => 7.5,
+        Technique::DigitNishio => 7.6,
+        Technique::AlsXyWing => 7.8,
+        Technique::DeathBlossom => 8.0,
+        Technique::CellForcingChain => 8.3,
+        Technique::UnitForcingChain => 8.5,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::strategies::test_util::sudoku;
+
+    #[test]
+    fn test_rate_beginner() {
+        // Seventeen givens, yet hidden singles alone solve it
+        let beginner = sudoku(
+            "000000010400000000020000000000050407008000300001090000300400200050100000000806000",
+        );
+        let rating = rate(&beginner);
+        assert!(rating.solved);
+        assert_eq!(rating.hardest_technique, Some(Technique::HiddenSingle));
+        assert_eq!(rating.difficulty(), Difficulty::Beginner);
+    }
+    #[test]
+    fn test_rate_easy() {
+        let easy = sudoku(
+            "091000000023000010500000000080003200070080006000001007800260005000305600010700090",
+        );
+        let rating = rate(&easy);
+        assert!(rating.solved);
+        assert_eq!(rating.hardest_technique, Some(Technique::NakedSingle));
+        assert!(rating.score > 0);
+        assert_eq!(rating.difficulty(), Difficulty::Easy);
+    }
+    #[test]
+    fn test_rate_medium() {
+        let medium = sudoku(
+            "470000000020301005010540006750009000040823000000010002900008003000000700000000020",
+        );
+        let rating = rate(&medium);
+        assert!(rating.solved);
+        assert_eq!(rating.difficulty(), Difficulty::Medium);
+    }
+    #[test]
+    fn test_rate_hard() {
+        let mut hard = sudoku(
+            "800006004004700000090003800630900700000000920009010005000009030000000248000401006",
+        );
+        hard.set_unique_solution(true);
+        let rating = rate(&hard);
+        assert!(rating.solved);
+        assert!(rating.hardest >= 4.5);
+        assert_eq!(rating.difficulty(), Difficulty::Diabolical);
+        // The rated copy is solved, the board itself is left alone
+        assert!(hard.get_board().iter().flatten().any(|&num| num == 0));
+    }
+    #[test]
+    fn test_difficulty_tiers() {
+        let rating = |hardest| Rating {
+            hardest,
+            hardest_technique: None,
+            score: 0,
+            solved: true,
+        };
+        assert_eq!(rating(1.5).difficulty(), Difficulty::Beginner);
+        assert_eq!(rating(2.3).difficulty(), Difficulty::Easy);
+        assert_eq!(rating(4.2).difficulty(), Difficulty::Hard);
+        assert_eq!(rating(5.7).difficulty(), Difficulty::Expert);
+        assert_eq!(rating(8.5).difficulty(), Difficulty::Extreme);
+        assert_eq!(rating(UNSOLVED_RATING).difficulty(), Difficulty::Extreme);
+    }
+    #[test]
+    fn test_rate_keeps_score_cumulative() {
+        let easy = sudoku(
+            "003020600900305001001806400008102900700000008006708200002609500800203009005010300",
+        );
+        let blanks = easy
+            .get_board()
+            .iter()
+            .flatten()
+            .filter(|&&n| n == 0)
+            .count() as u32;
+        // Every blank needs at least a naked single
+        assert!(rate(&easy).score >= blanks * 4);
+    }
+}
@@ -0,0 +1,243 @@
+use crate::sudoku::{ALL_CANDIDATES, House, box_of};
+use std::sync::OnceLock;
+
+/// Cells of every house as indices from 0 to 80, in `House::all` order
+fn houses() -> &'static [[usize; 9]; 27] {
+    static HOUSES: OnceLock<[[usize; 9]; 27]> = OnceLock::new();
+    HOUSES.get_or_init(|| {
+        let mut houses = [[0; 9]; 27];
+        for (house, cells) in House::all().zip(houses.iter_mut()) {
+            *cells = house.cells().map(|(r, c)| r * 9 + c);
+        }
+        houses
+    })
+}
+
+/// Board for the fast solver. Digits used in every row, column and box are kept as
+/// bitmasks so the candidates of a cell are a few bit operations away.
+#[derive(Debug, Clone)]
+pub struct Bitboard {
+    cells: [u8; 81],
+    rows: [u16; 9],
+    cols: [u16; 9],
+    boxes: [u16; 9],
+}
+
+impl Bitboard {
+    /// `None` when two givens already clash
+    pub fn new(board: [[usize; 9]; 9]) -> Option<Self> {
+        let mut bitboard = Self {
+            cells: [0; 81],
+            rows: [0; 9],
+            cols: [0; 9],
+            boxes: [0; 9],
+        };
+        for (i, &num) in board.iter().flatten().enumerate() {
+            if num == 0 {
+                continue;
+            }
+            if num > 9 || bitboard.candidates(i) & (1 << (num - 1)) == 0 {
+                return None;
+            }
+            bitboard.place(i, num);
+        }
+        Some(bitboard)
+    }
+    pub fn board(&self) -> [[usize; 9]; 9] {
+        std::array::from_fn(|r| std::array::from_fn(|c| self.cells[r * 9 + c] as usize))
+    }
+    fn candidates(&self, i: usize) -> u16 {
+        let (row, col) = (i / 9, i % 9);
+        ALL_CANDIDATES & !(self.rows[row] | self.cols[col] | self.boxes[box_of(row, col)])
+    }
+    fn place(&mut self, i: usize, num: usize) {
+        let (row, col) = (i / 9, i % 9);
+        let bit = 1 << (num - 1);
+        self.cells[i] = num as u8;
+        self.rows[row] |= bit;
+        self.cols[col] |= bit;
+        self.boxes[box_of(row, col)] |= bit;
+    }
+    /// Places naked and hidden singles until none are left. Returns false when a cell or a
+    /// digit in some house runs out of places.
+    fn propagate(&mut self) -> bool {
+        loop {
+            let mut progress = false;
+            for i in 0..81 {
+                if self.cells[i] != 0 {
+                    continue;
+                }
+                let mask = self.candidates(i);
+                match mask.count_ones() {
+                    0 => return false,
+                    1 => {
+                        self.place(i, mask.trailing_zeros() as usize + 1);
+                        progress = true;
+                    }
+                    _ => {}
+                }
+            }
+            for cells in houses() {
+                // Digits seen in at least one, and in at least two of the empty cells
+                let (mut once, mut twice, mut placed) = (0u16, 0u16, 0u16);
+                for &i in cells {
+                    if self.cells[i] != 0 {
+                        placed |= 1 << (self.cells[i] - 1);
+                        continue;
+                    }
+                    let mask = self.candidates(i);
+                    twice |= once & mask;
+                    once |= mask;
+                }
+                if once | placed != ALL_CANDIDATES {
+                    return false;
+                }
+                let mut hidden = once & !twice;
+                while hidden != 0 {
+                    let bit = hidden & hidden.wrapping_neg();
+                    hidden &= hidden - 1;
+                    // An earlier single in this house may have taken the cell or the digit
+                    if let Some(&i) = cells
+                        .iter()
+                        .find(|&&i| self.cells[i] == 0 && self.candidates(i) & bit != 0)
+                    {
+                        self.place(i, bit.trailing_zeros() as usize + 1);
+                        progress = true;
+                    } else {
+                        return false;
+                    }
+                }
+            }
+            if !progress {
+                return true;
+            }
+        }
+    }
+    /// Depth first search branching on the cell with the fewest candidates. `found` is called
+    /// with every solution and returns true to stop the search, which is then returned.
+    pub fn search(&self, found: &mut dyn FnMut(&Bitboard) -> bool) -> bool {
+        let mut board = self.clone();
+        if !board.propagate() {
+            return false;
+        }
+        let Some(i) = (0..81)
+            .filter(|&i| board.cells[i] == 0)
+            .min_by_key(|&i| board.candidates(i).count_ones())
+        else {
+            return found(&board);
+        };
+        let mut mask = board.candidates(i);
+        while mask != 0 {
+            let num = mask.trailing_zeros() as usize + 1;
+            mask &= mask - 1;
+            let mut branch = board.clone();
+            branch.place(i, num);
+            if branch.search(found) {
+                return true;
+            }
+        }
+        false
+    }
+}
+
+/// Solves the board straight away, without the animation of `brute_force`. `None` when the
+/// board has no solution.
+pub fn solve(board: [[usize; 9]; 9]) -> Option<[[usize; 9]; 9]> {
+    let mut solution = None;
+    Bitboard::new(board)?.search(&mut |bitboard| {
+        solution = Some(bitboard.board());
+        true
+    });
+    solution
+}
+
+/// Number of solutions of the board, counting no further than `limit`
+pub fn count_solutions(board: [[usize; 9]; 9], limit: usize) -> usize {
+    let mut count = 0;
+    if limit == 0 {
+        return count;
+    }
+    if let Some(bitboard) = Bitboard::new(board) {
+        bitboard.search(&mut |_| {
+            count += 1;
+            count >= limit
+        });
+    }
+    count
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solver::test_util::parse;
+
+    fn is_valid(solution: &[[usize; 9]; 9], puzzle: &[[usize; 9]; 9]) -> bool {
+        House::all().all(|house| {
+            let mask = house
+                .cells()
+                .iter()
+                .fold(0u16, |mask, &(r, c)| mask | 1 << (solution[r][c] - 1));
+            mask == ALL_CANDIDATES
+        }) && (0..81).all(|i| {
+            let (r, c) = (i / 9, i % 9);
+            puzzle[r][c] == 0 || puzzle[r][c] == solution[r][c]
+        })
+    }
+
+    #[test]
+    fn test_solve_easy() {
+        let puzzle = parse(
+            "003020600900305001001806400008102900700000008006708200002609500800203009005010300",
+        );
+        let solution = solve(puzzle).unwrap();
+        assert!(is_valid(&solution, &puzzle));
+        assert_eq!(solution[0], [4, 8, 3, 9, 2, 1, 6, 5, 7]);
+    }
+    #[test]
+    fn test_solve_hardest() {
+        // AI Escargot, Easter Monster and Platinum Blonde
+        for puzzle in [
+            "100007090030020008009600500005300900010080002600004000300000010040000007007000300",
+            "100000002090400050006000700050903000000070000000850040700000600030009080002000001",
+            "000000012000000003002300400001800005060070800000009000008500000900040500470006000",
+        ] {
+            let puzzle = parse(puzzle);
+            let solution = solve(puzzle).unwrap();
+            assert!(is_valid(&solution, &puzzle));
+        }
+    }
+    #[test]
+    fn test_solve_empty() {
+        let solution = solve([[0; 9]; 9]).unwrap();
+        assert!(is_valid(&solution, &[[0; 9]; 9]));
+    }
+    #[test]
+    fn test_solve_impossible() {
+        let mut puzzle = [[0; 9]; 9];
+        puzzle[0][0] = 5;
+        puzzle[0][8] = 5;
+        assert!(Bitboard::new(puzzle).is_none());
+        assert_eq!(solve(puzzle), None);
+        // Row 1 leaves r1c9 only a 9, which column 9 already holds
+        let puzzle = parse(
+            "123456780000000009000000000000000000000000000000000000000000000000000000000000000",
+        );
+        assert_eq!(solve(puzzle), None);
+        assert_eq!(count_solutions(puzzle, 2), 0);
+    }
+    #[test]
+    fn test_count_solutions() {
+        let mut board = parse(
+            "483921657967345821251876493548132976729564138136798245372689514814253769695417382",
+        );
+        assert_eq!(count_solutions(board, 2), 1);
+        // 8 and 6 can swap places in r1c2, r1c7, r2c2 and r2c7
+        for (row, col) in [(0, 1), (0, 6), (1, 1), (1, 6)] {
+            board[row][col] = 0;
+        }
+        assert_eq!(count_solutions(board, 10), 2);
+        assert_eq!(count_solutions(board, 1), 1);
+        assert_eq!(count_solutions([[0; 9]; 9], 50), 50);
+        assert_eq!(count_solutions([[0; 9]; 9], 0), 0);
+    }
+}
@@ -0,0 +1,269 @@
+/// Exact cover problem solved with Knuth's Algorithm X on dancing links. Every column has to
+/// be covered by exactly one of the chosen options.
+pub struct Dlx {
+    left: Vec<usize>,
+    right: Vec<usize>,
+    up: Vec<usize>,
+    down: Vec<usize>,
+    /// Header of the column a node sits in
+    column: Vec<usize>,
+    /// Option a node belongs to, unused for headers
+    option: Vec<usize>,
+    /// Nodes left in every column, indexed by header
+    size: Vec<usize>,
+    options: usize,
+}
+
+impl Dlx {
+    /// Matrix with the given number of columns and no options. Node 0 is the root, nodes 1 up
+    /// to `columns` head the columns.
+    pub fn new(columns: usize) -> Self {
+        let n = columns + 1;
+        Self {
+            left: (0..n).map(|i| (i + n - 1) % n).collect(),
+            right: (0..n).map(|i| (i + 1) % n).collect(),
+            up: (0..n).collect(),
+            down: (0..n).collect(),
+            column: (0..n).collect(),
+            option: vec![usize::MAX; n],
+            size: vec![0; n],
+            options: 0,
+        }
+    }
+    /// Adds an option covering the given columns and returns its index
+    pub fn add_option(&mut self, columns: &[usize]) -> usize {
+        assert!(!columns.is_empty(), "an option has to cover a column");
+        let id = self.options;
+        self.options += 1;
+        let first = self.left.len();
+        let last = first + columns.len() - 1;
+        for (k, &col) in columns.iter().enumerate() {
+            let head = col + 1;
+            let node = first + k;
+            let above = self.up[head];
+            self.up.push(above);
+            self.down.push(head);
+            self.down[above] = node;
+            self.up[head] = node;
+            self.left.push(if node == first { last } else { node - 1 });
+            self.right.push(if node == last { first } else { node + 1 });
+            self.column.push(head);
+            self.option.push(id);
+            self.size[head] += 1;
+        }
+        id
+    }
+    fn cover(&mut self, head: usize) {
+        let (left, right) = (self.left[head], self.right[head]);
+        self.right[left] = right;
+        self.left[right] = left;
+        let mut i = self.down[head];
+        while i != head {
+            let mut j = self.right[i];
+            while j != i {
+                let (up, down) = (self.up[j], self.down[j]);
+                self.down[up] = down;
+                self.up[down] = up;
+                self.size[self.column[j]] -= 1;
+                j = self.right[j];
+            }
+            i = self.down[i];
+        }
+    }
+    fn uncover(&mut self, head: usize) {
+        let mut i = self.up[head];
+        while i != head {
+            let mut j = self.left[i];
+            while j != i {
+                self.size[self.column[j]] += 1;
+                let (up, down) = (self.up[j], self.down[j]);
+                self.down[up] = j;
+                self.up[down] = j;
+                j = self.left[j];
+            }
+            i = self.up[i];
+        }
+        let (left, right) = (self.left[head], self.right[head]);
+        self.right[left] = head;
+        self.left[right] = head;
+    }
+    /// Calls `found` with the options of every exact cover until it returns true, which is
+    /// then returned. The matrix is left as it was.
+    pub fn search(&mut self, found: &mut dyn FnMut(&[usize]) -> bool) -> bool {
+        self.search_from(&mut vec![], found)
+    }
+    fn search_from(
+        &mut self,
+        chosen: &mut Vec<usize>,
+        found: &mut dyn FnMut(&[usize]) -> bool,
+    ) -> bool {
+        if self.right[0] == 0 {
+            return found(chosen);
+        }
+        // Branch on the column with the fewest options left
+        let mut head = self.right[0];
+        let mut col = self.right[head];
+        while col != 0 {
+            if self.size[col] < self.size[head] {
+                head = col;
+            }
+            col = self.right[col];
+        }
+        if self.size[head] == 0 {
+            return false;
+        }
+        self.cover(head);
+        let mut stop = false;
+        let mut row = self.down[head];
+        while row != head && !stop {
+            chosen.push(self.option[row]);
+            let mut j = self.right[row];
+            while j != row {
+                self.cover(self.column[j]);
+                j = self.right[j];
+            }
+            stop = self.search_from(chosen, found);
+            let mut j = self.left[row];
+            while j != row {
+                self.uncover(self.column[j]);
+                j = self.left[j];
+            }
+            chosen.pop();
+            row = self.down[row];
+        }
+        self.uncover(head);
+        stop
+    }
+}
+
+/// Sudoku as exact cover: an option places a digit in a cell and covers that cell plus the
+/// digit in its row, column and box. Givens only get the option for their own digit. The
+/// placement of every option is returned by option index.
+fn sudoku_matrix(board: &[[usize; 9]; 9]) -> (Dlx, Vec<(usize, usize, usize)>) {
+    let mut dlx = Dlx::new(4 * 81);
+    let mut placements = vec![];
+    for (row, nums) in board.iter().enumerate() {
+        for (col, &given) in nums.iter().enumerate() {
+            let b = crate::sudoku::box_of(row, col);
+            for num in (1..=9).filter(|&num| given == 0 || given == num) {
+                let d = num - 1;
+                dlx.add_option(&[
+                    row * 9 + col,
+                    81 + row * 9 + d,
+                    162 + col * 9 + d,
+                    243 + b * 9 + d,
+                ]);
+                placements.push((row, col, num));
+            }
+        }
+    }
+    (dlx, placements)
+}
+
+/// Up to `limit` solutions of the board
+pub fn solutions(board: [[usize; 9]; 9], limit: usize) -> Vec<[[usize; 9]; 9]> {
+    let mut solutions = vec![];
+    if limit == 0 {
+        return solutions;
+    }
+    let (mut dlx, placements) = sudoku_matrix(&board);
+    dlx.search(&mut |options| {
+        let mut solution = [[0; 9]; 9];
+        for &option in options {
+            let (row, col, num) = placements[option];
+            solution[row][col] = num;
+        }
+        solutions.push(solution);
+        solutions.len() >= limit
+    });
+    solutions
+}
+
+/// Number of solutions of the board, counting no further than `limit`
+pub fn count(board: [[usize; 9]; 9], limit: usize) -> usize {
+    solutions(board, limit).len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solver::test_util::parse;
+
+    #[test]
+    fn test_exact_cover() {
+        // Knuth's example, whose only cover is options 0, 3 and 4
+        let mut dlx = Dlx::new(7);
+        for option in [
+            vec![2, 4, 5],
+            vec![0, 3, 6],
+            vec![1, 2, 5],
+            vec![0, 3],
+            vec![1, 6],
+            vec![3, 4, 6],
+        ] {
+            dlx.add_option(&option);
+        }
+        let mut covers = vec![];
+        dlx.search(&mut |options| {
+            let mut options = options.to_vec();
+            options.sort();
+            covers.push(options);
+            false
+        });
+        assert_eq!(covers, vec![vec![0, 3, 4]]);
+    }
+    #[test]
+    #[should_panic]
+    fn test_empty_option() {
+        Dlx::new(3).add_option(&[]);
+    }
+    #[test]
+    fn test_count_unique() {
+        let board = parse(
+            "003020600900305001001806400008102900700000008006708200002609500800203009005010300",
+        );
+        assert_eq!(count(board, 10), 1);
+        let solved = solutions(board, 10);
+        assert_eq!(solved.len(), 1);
+        assert_eq!(solved[0][0], [4, 8, 3, 9, 2, 1, 6, 5, 7]);
+    }
+    #[test]
+    fn test_count_deadly_pattern() {
+        let mut board = parse(
+            "483921657967345821251876493548132976729564138136798245372689514814253769695417382",
+        );
+        // 8 and 6 swap places in r1c2, r1c7, r2c2 and r2c7
+        for (row, col) in [(0, 1), (0, 6), (1, 1), (1, 6)] {
+            board[row][col] = 0;
+        }
+        assert_eq!(count(board, 10), 2);
+        assert_eq!(count(board, 1), 1);
+        let solved = solutions(board, 10);
+        assert_eq!(solved.len(), 2);
+        assert_ne!(solved[0], solved[1]);
+    }
+    #[test]
+    fn test_count_limits() {
+        assert_eq!(count([[0; 9]; 9], 25), 25);
+        assert_eq!(count([[0; 9]; 9], 0), 0);
+        let mut board = [[0; 9]; 9];
+        board[0][0] = 5;
+        board[8][0] = 5;
+        assert_eq!(count(board, 10), 0);
+        assert!(solutions(board, 10).is_empty());
+    }
+    #[test]
+    fn test_agrees_with_bitboard() {
+        for puzzle in [
+            "100007090030020008009600500005300900010080002600004000300000010040000007007000300",
+            "000000012000000003002300400001800005060070800000009000008500000900040500470006000",
+            "800006004004700000090003800630900700000000920009010005000009030000000248000401006",
+        ] {
+            let board = parse(puzzle);
+            assert_eq!(
+                solutions(board, 2),
+                vec![crate::solver::solve(board).unwrap()]
+            );
+        }
+    }
+}
@@ -3,6 +3,11 @@ use iced::{
     stream::try_channel,
 };
 
+mod bitboard;
+pub mod dlx;
+
+pub use bitboard::{count_solutions, solve};
+
 pub fn brute_force(
     mut board: [[usize; 9]; 9],
     puzzle: [[usize; 9]; 9],
@@ -80,3 +85,12 @@ pub fn brute_force(
         Ok(())
     })
 }
+
+#[cfg(test)]
+pub mod test_util {
+    /// Parses an 81 digit puzzle where `0` is an empty cell
+    pub fn parse(puzzle: &str) -> [[usize; 9]; 9] {
+        let digits: Vec<usize> = puzzle.bytes().map(|b| (b - b'0') as usize).collect();
+        std::array::from_fn(|r| std::array::from_fn(|c| digits[r * 9 + c]))
+    }
+}
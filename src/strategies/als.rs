@@ -0,0 +1,435 @@
+use std::collections::HashSet;
+use std::sync::OnceLock;
+
+use super::{Cell, Detail, Step, Technique, cell_name, combinations, digits_name};
+use crate::sudoku::{ALL_CANDIDATES, House, Sudoku, bit, digits, peers};
+
+/// Stem cells with more candidates than this are not tried as a Death Blossom
+const MAX_STEM_CANDIDATES: u32 = 3;
+
+/// Almost locked set: N unsolved cells in one house holding N + 1 digits between them.
+/// Losing any one of its digits locks the rest into the set.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Als {
+    pub cells: Vec<Cell>,
+    pub digits: u16,
+}
+
+/// Cells of the grid as a bitmask, bit `9 * row + col`
+type Cells = u128;
+
+fn cell_bit((row, col): Cell) -> Cells {
+    1 << (row * 9 + col)
+}
+
+fn cells_of(mask: Cells) -> impl Iterator<Item = Cell> {
+    (0..81)
+        .filter(move |i| mask & (1 << i) != 0)
+        .map(|i| (i / 9, i % 9))
+}
+
+fn peer_mask((row, col): Cell) -> Cells {
+    static PEERS: OnceLock<[Cells; 81]> = OnceLock::new();
+    PEERS.get_or_init(|| {
+        std::array::from_fn(|i| peers(i / 9, i % 9).fold(0, |mask, cell| mask | cell_bit(cell)))
+    })[row * 9 + col]
+}
+
+/// An ALS with per digit masks of the cells holding the digit and of the cells that see all
+/// of them
+struct Set {
+    als: Als,
+    cells: Cells,
+    holders: [Cells; 10],
+    seen: [Cells; 10],
+}
+
+impl Set {
+    fn new(cells: Vec<Cell>, candidates: &[[u16; 9]; 9]) -> Self {
+        let mut set = Set {
+            als: Als {
+                digits: cells
+                    .iter()
+                    .fold(0, |mask, &(r, c)| mask | candidates[r][c]),
+                cells,
+            },
+            cells: 0,
+            holders: [0; 10],
+            seen: [0; 10],
+        };
+        set.seen = [!0; 10];
+        for &(r, c) in &set.als.cells {
+            set.cells |= cell_bit((r, c));
+            for num in digits(candidates[r][c]) {
+                set.holders[num] |= cell_bit((r, c));
+                set.seen[num] &= peer_mask((r, c));
+            }
+        }
+        set
+    }
+    fn has(&self, num: usize) -> bool {
+        self.als.digits & bit(num) != 0
+    }
+    /// Digits whose every candidate in `self` sees every candidate in `other`, so at most
+    /// one of the two sets can hold them
+    fn restricted(&self, other: &Set) -> u16 {
+        if self.cells & other.cells != 0 {
+            return 0;
+        }
+        digits(self.als.digits & other.als.digits)
+            .filter(|&num| other.holders[num] & !self.seen[num] == 0)
+            .fold(0, |mask, num| mask | bit(num))
+    }
+}
+
+/// Every ALS of every house, each set of cells only once
+fn almost_locked_sets(candidates: &[[u16; 9]; 9]) -> Vec<Set> {
+    let mut found = HashSet::new();
+    let mut sets = vec![];
+    for house in House::all() {
+        let unsolved: Vec<Cell> = house
+            .cells()
+            .into_iter()
+            .filter(|&(r, c)| candidates[r][c] != 0)
+            .collect();
+        for size in 1..unsolved.len() {
+            for cells in combinations(&unsolved, size) {
+                let mask = cells
+                    .iter()
+                    .fold(0, |mask, &(r, c)| mask | candidates[r][c]);
+                if mask.count_ones() as usize != size + 1 {
+                    continue;
+                }
+                let set = Set::new(cells, candidates);
+                if found.insert(set.cells) {
+                    sets.push(set);
+                }
+            }
+        }
+    }
+    sets
+}
+
+/// Digit masks of the grid as cell masks, indexed by digit
+fn digit_cells(candidates: &[[u16; 9]; 9]) -> [Cells; 10] {
+    let mut cells = [0; 10];
+    for (row, col) in (0..81).map(|i| (i / 9, i % 9)) {
+        for num in digits(candidates[row][col]) {
+            cells[num] |= cell_bit((row, col));
+        }
+    }
+    cells
+}
+
+/// Candidates of `num` outside the sets that see every `num` in all of them
+fn common_eliminations(sets: &[&Set], num: usize, by_digit: &[Cells; 10]) -> Vec<(Cell, usize)> {
+    let mut targets = by_digit[num];
+    for set in sets {
+        targets &= set.seen[num] & !set.cells;
+    }
+    cells_of(targets).map(|cell| (cell, num)).collect()
+}
+
+/// Two ALS joined by a restricted common digit x. x is in at most one of them, so the other
+/// is locked and any shared digit z ends up in one of the two sets. With two restricted
+/// digits both sets are locked and every digit of them is confined.
+pub fn als_xz(sudoku: &Sudoku) -> Option<Step> {
+    let candidates = sudoku.get_candidates();
+    let by_digit = digit_cells(&candidates);
+    let sets = almost_locked_sets(&candidates);
+    for (i, a) in sets.iter().enumerate() {
+        for b in &sets[i + 1..] {
+            let eliminations = xz_eliminations(a, b, &by_digit);
+            if !eliminations.is_empty() {
+                let links = digits(a.restricted(b)).collect();
+                return als_step(Technique::AlsXz, &[a, b], links, None, eliminations);
+            }
+        }
+    }
+    None
+}
+
+fn xz_eliminations(a: &Set, b: &Set, by_digit: &[Cells; 10]) -> Vec<(Cell, usize)> {
+    let restricted = a.restricted(b);
+    let mut eliminations = vec![];
+    match restricted.count_ones() {
+        0 => {}
+        1 => {
+            for z in digits(a.als.digits & b.als.digits & !restricted) {
+                eliminations.extend(common_eliminations(&[a, b], z, by_digit));
+            }
+        }
+        _ => {
+            for x in digits(restricted) {
+                eliminations.extend(common_eliminations(&[a, b], x, by_digit));
+            }
+            for (set, other) in [(a, b), (b, a)] {
+                for z in digits(set.als.digits & !restricted) {
+                    eliminations.extend(
+                        common_eliminations(&[set], z, by_digit)
+                            .into_iter()
+                            .filter(|&(cell, _)| other.cells & cell_bit(cell) == 0),
+                    );
+                }
+            }
+        }
+    }
+    eliminations
+}
+
+/// A pivot ALS restricted to two wings on different digits x and y. Whichever way the pivot
+/// goes one wing is locked, so a digit z shared by the wings goes from every cell seeing
+/// all of its candidates in both.
+pub fn als_xy_wing(sudoku: &Sudoku) -> Option<Step> {
+    let candidates = sudoku.get_candidates();
+    let by_digit = digit_cells(&candidates);
+    let sets = almost_locked_sets(&candidates);
+    for pivot in &sets {
+        let wings: Vec<(&Set, usize)> = sets
+            .iter()
+            .flat_map(|set| digits(pivot.restricted(set)).map(move |num| (set, num)))
+            .collect();
+        for (i, &(a, x)) in wings.iter().enumerate() {
+            for &(b, y) in &wings[i + 1..] {
+                if x == y || a.cells & b.cells != 0 {
+                    continue;
+                }
+                let mut eliminations = vec![];
+                for z in digits(a.als.digits & b.als.digits & !bit(x) & !bit(y)) {
+                    eliminations.extend(
+                        common_eliminations(&[a, b], z, &by_digit)
+                            .into_iter()
+                            .filter(|&(cell, _)| pivot.cells & cell_bit(cell) == 0),
+                    );
+                }
+                if !eliminations.is_empty() {
+                    return als_step(
+                        Technique::AlsXyWing,
+                        &[a, pivot, b],
+                        vec![x, y],
+                        None,
+                        eliminations,
+                    );
+                }
+            }
+        }
+    }
+    None
+}
+
+/// A stem cell whose every candidate d sees all of d in its own petal ALS. The stem takes one
+/// of them, locking that petal, so a digit z in every petal goes from the cells that see all
+/// of its candidates in the petals.
+pub fn death_blossom(sudoku: &Sudoku) -> Option<Step> {
+    let candidates = sudoku.get_candidates();
+    let by_digit = digit_cells(&candidates);
+    let sets = almost_locked_sets(&candidates);
+    for stem in (0..81).map(|i| (i / 9, i % 9)) {
+        let mask = candidates[stem.0][stem.1];
+        if mask.count_ones() < 2 || mask.count_ones() > MAX_STEM_CANDIDATES {
+            continue;
+        }
+        let stem_peers = peer_mask(stem);
+        let petals: Vec<Vec<&Set>> = digits(mask)
+            .map(|num| {
+                sets.iter()
+                    .filter(|set| set.has(num) && set.holders[num] & !stem_peers == 0)
+                    .filter(|set| set.cells & cell_bit(stem) == 0)
+                    .collect()
+            })
+            .collect();
+        let stem_digits: Vec<usize> = digits(mask).collect();
+        let mut chosen = vec![];
+        if let Some(step) = blossom(
+            stem,
+            &stem_digits,
+            &petals,
+            &mut chosen,
+            0,
+            ALL_CANDIDATES,
+            &by_digit,
+        ) {
+            return Some(step);
+        }
+    }
+    None
+}
+
+/// Picks one petal per stem digit depth first, keeping the digits common to every petal so
+/// far in `shared`
+fn blossom<'a>(
+    stem: Cell,
+    stem_digits: &[usize],
+    petals: &[Vec<&'a Set>],
+    chosen: &mut Vec<&'a Set>,
+    used: Cells,
+    shared: u16,
+    by_digit: &[Cells; 10],
+) -> Option<Step> {
+    let depth = chosen.len();
+    if depth == petals.len() {
+        let mut eliminations = vec![];
+        for z in digits(shared) {
+            eliminations.extend(common_eliminations(chosen, z, by_digit));
+        }
+        if eliminations.is_empty() {
+            return None;
+        }
+        return als_step(
+            Technique::DeathBlossom,
+            chosen,
+            stem_digits.to_vec(),
+            Some(stem),
+            eliminations,
+        );
+    }
+    for &petal in &petals[depth] {
+        let shared = shared & petal.als.digits & !bit(stem_digits[depth]);
+        if shared == 0 || used & petal.cells != 0 {
+            continue;
+        }
+        chosen.push(petal);
+        let step = blossom(
+            stem,
+            stem_digits,
+            petals,
+            chosen,
+            used | petal.cells,
+            shared,
+            by_digit,
+        );
+        chosen.pop();
+        if step.is_some() {
+            return step;
+        }
+    }
+    None
+}
+
+fn als_name(als: &Als) -> String {
+    let cells: Vec<String> = als.cells.iter().map(|&cell| cell_name(cell)).collect();
+    format!("{} {}", cells.join(","), digits_name(als.digits))
+}
+
+fn als_step(
+    technique: Technique,
+    sets: &[&Set],
+    links: Vec<usize>,
+    stem: Option<Cell>,
+    mut eliminations: Vec<(Cell, usize)>,
+) -> Option<Step> {
+    eliminations.sort();
+    eliminations.dedup();
+    if eliminations.is_empty() {
+        return None;
+    }
+    let sets: Vec<Als> = sets.iter().map(|set| set.als.clone()).collect();
+    let mut highlights: Vec<(Cell, u16)> = sets
+        .iter()
+        .flat_map(|als| als.cells.iter().map(|&cell| (cell, als.digits)))
+        .collect();
+    let links_name = links
+        .iter()
+        .map(|num| num.to_string())
+        .collect::<Vec<_>>()
+        .join(",");
+    let sets_name = sets.iter().map(als_name).collect::<Vec<_>>().join(" and ");
+    let explanation = match stem {
+        Some(cell) => {
+            highlights.push((cell, links.iter().fold(0, |mask, &num| mask | bit(num))));
+            format!(
+                "{}: stem {} with petals {} on {}",
+                technique.name(),
+                cell_name(cell),
+                sets_name,
+                links_name
+            )
+        }
+        None => format!(
+            "{}: {} restricted on {}",
+            technique.name(),
+            sets_name,
+            links_name
+        ),
+    };
+    Some(Step {
+        technique,
+        placements: vec![],
+        eliminations,
+        highlights,
+        explanation,
+        detail: Detail::Als { sets, links, stem },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::strategies::test_util::keep;
+
+    #[test]
+    fn test_almost_locked_sets() {
+        let mut sudoku = Sudoku::new();
+        keep(&mut sudoku, (0, 0), &[1, 2]);
+        keep(&mut sudoku, (0, 1), &[2, 3]);
+        let sets = almost_locked_sets(&sudoku.get_candidates());
+        assert!(sets.iter().any(|set| set.als.cells == vec![(0, 0)]));
+        assert!(sets.iter().any(|set| set.als.cells == vec![(0, 0), (0, 1)]));
+        // Found in both row 1 and box 1 but listed once
+        let pairs = sets
+            .iter()
+            .filter(|set| set.als.cells == vec![(0, 0), (0, 1)])
+            .count();
+        assert_eq!(pairs, 1);
+    }
+    #[test]
+    fn test_als_xz() {
+        let mut sudoku = Sudoku::new();
+        keep(&mut sudoku, (0, 0), &[1, 2]);
+        keep(&mut sudoku, (0, 1), &[2, 3]);
+        keep(&mut sudoku, (4, 0), &[1, 3]);
+        let candidates = sudoku.get_candidates();
+        let a = Set::new(vec![(0, 0), (0, 1)], &candidates);
+        let b = Set::new(vec![(4, 0)], &candidates);
+        assert_eq!(a.restricted(&b), bit(1));
+        let mut eliminations = xz_eliminations(&a, &b, &digit_cells(&candidates));
+        eliminations.sort();
+        assert_eq!(
+            eliminations,
+            vec![
+                ((1, 0), 3),
+                ((2, 0), 3),
+                ((3, 1), 3),
+                ((4, 1), 3),
+                ((5, 1), 3)
+            ]
+        );
+    }
+    #[test]
+    fn test_doubly_linked_als_xz() {
+        let mut sudoku = Sudoku::new();
+        keep(&mut sudoku, (0, 0), &[1, 2]);
+        keep(&mut sudoku, (0, 1), &[2, 3]);
+        keep(&mut sudoku, (0, 5), &[1, 3]);
+        let candidates = sudoku.get_candidates();
+        let a = Set::new(vec![(0, 0), (0, 1)], &candidates);
+        let b = Set::new(vec![(0, 5)], &candidates);
+        assert_eq!(a.restricted(&b), bit(1) | bit(3));
+        let eliminations = xz_eliminations(&a, &b, &digit_cells(&candidates));
+        // Row 1 loses 1 and 3, and 2 is locked into box 1
+        assert!(eliminations.contains(&((0, 8), 1)));
+        assert!(eliminations.contains(&((0, 8), 3)));
+        assert!(eliminations.contains(&((1, 1), 2)));
+        assert!(!eliminations.contains(&((1, 1), 1)));
+    }
+    #[test]
+    fn test_death_blossom_on_digit_1() {
+        let mut sudoku = Sudoku::new();
+        keep(&mut sudoku, (4, 4), &[2, 3]);
+        keep(&mut sudoku, (4, 0), &[1, 2]);
+        keep(&mut sudoku, (0, 4), &[1, 3]);
+        let step = death_blossom(&sudoku).unwrap();
+        assert_eq!(step.technique, Technique::DeathBlossom);
+        assert_eq!(step.eliminations, vec![((0, 0), 1)]);
+    }
+}
@@ -0,0 +1,420 @@
+use super::{Cell, Detail, Intersection, Step, Technique, cell_name, seen_by_all, sees};
+use crate::sudoku::{House, Sudoku, bit, digits};
+
+/// Default cap on the number of nodes in a chain
+pub const MAX_CHAIN_LENGTH: usize = 12;
+
+/// A candidate, or a group of candidates of one digit inside a box/line intersection that
+/// acts as a single candidate
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Node {
+    pub digit: usize,
+    pub cells: Vec<Cell>,
+}
+
+impl Node {
+    /// Node in Eureka notation without its digit, e.g. `r1c2` or `r1c23` for a group
+    fn cells_name(&self) -> String {
+        if let [cell] = self.cells[..] {
+            return cell_name(cell);
+        }
+        let rows: Vec<String> = unique(self.cells.iter().map(|c| c.0 + 1));
+        let cols: Vec<String> = unique(self.cells.iter().map(|c| c.1 + 1));
+        format!("r{}c{}", rows.concat(), cols.concat())
+    }
+    fn is_group(&self) -> bool {
+        self.cells.len() > 1
+    }
+}
+
+fn unique(values: impl Iterator<Item = usize>) -> Vec<String> {
+    let mut values: Vec<usize> = values.collect();
+    values.dedup();
+    values.iter().map(|v| v.to_string()).collect()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ChainKind {
+    /// Single digit chains over conjugate pairs
+    XCycle,
+    /// Bivalue cells joined through a shared digit
+    XYChain,
+    /// Any mix of candidates, bivalue cells and grouped nodes
+    Aic,
+}
+
+/// Single digit chains of strong and weak links, including continuous loops
+pub fn x_cycle(sudoku: &Sudoku, max_length: usize) -> Option<Step> {
+    find_chain(sudoku, ChainKind::XCycle, max_length)
+}
+
+/// Chains of bivalue cells where each cell hands its other digit on to the next one
+pub fn xy_chain(sudoku: &Sudoku, max_length: usize) -> Option<Step> {
+    find_chain(sudoku, ChainKind::XYChain, max_length)
+}
+
+/// General alternating inference chains mixing digits, cells and grouped nodes
+pub fn aic(sudoku: &Sudoku, max_length: usize) -> Option<Step> {
+    find_chain(sudoku, ChainKind::Aic, max_length)
+}
+
+/// Nodes together with their strong and weak links.
+/// Every strong link between two nodes that see each other is also usable as a weak one.
+struct Graph {
+    nodes: Vec<Node>,
+    strong: Vec<Vec<usize>>,
+    weak: Vec<Vec<usize>>,
+}
+
+impl Graph {
+    fn new(candidates: &[[u16; 9]; 9], kind: ChainKind) -> Self {
+        let mut nodes = vec![];
+        for (row, col) in (0..81).map(|i| (i / 9, i % 9)) {
+            let mask = candidates[row][col];
+            if kind == ChainKind::XYChain && mask.count_ones() != 2 {
+                continue;
+            }
+            for num in digits(mask) {
+                nodes.push(Node {
+                    digit: num,
+                    cells: vec![(row, col)],
+                });
+            }
+        }
+        if kind == ChainKind::Aic {
+            for intersection in Intersection::all() {
+                for num in 1..=9 {
+                    let cells: Vec<Cell> = intersection
+                        .cells()
+                        .into_iter()
+                        .filter(|&(r, c)| candidates[r][c] & bit(num) != 0)
+                        .collect();
+                    if cells.len() > 1 {
+                        nodes.push(Node { digit: num, cells });
+                    }
+                }
+            }
+        }
+        let mut graph = Graph {
+            strong: vec![vec![]; nodes.len()],
+            weak: vec![vec![]; nodes.len()],
+            nodes,
+        };
+        graph.link(candidates, kind);
+        graph
+    }
+
+    fn link(&mut self, candidates: &[[u16; 9]; 9], kind: ChainKind) {
+        let nodes = &self.nodes;
+        for a in 0..nodes.len() {
+            for b in a + 1..nodes.len() {
+                let (x, y) = (&nodes[a], &nodes[b]);
+                let (strong, weak) = if x.digit == y.digit {
+                    let disjoint = x.cells.iter().all(|c| !y.cells.contains(c));
+                    let peers =
+                        disjoint && x.cells.iter().all(|&p| y.cells.iter().all(|&q| sees(p, q)));
+                    let conjugate = peers
+                        && kind != ChainKind::XYChain
+                        && House::all().any(|house| {
+                            let mut holders: Vec<Cell> = house
+                                .cells()
+                                .into_iter()
+                                .filter(|&(r, c)| candidates[r][c] & bit(x.digit) != 0)
+                                .collect();
+                            let mut both: Vec<Cell> =
+                                x.cells.iter().chain(&y.cells).copied().collect();
+                            holders.sort();
+                            both.sort();
+                            holders == both
+                        });
+                    (conjugate, peers)
+                } else if kind != ChainKind::XCycle && !x.is_group() && x.cells == y.cells {
+                    let (row, col) = x.cells[0];
+                    (
+                        candidates[row][col].count_ones() == 2,
+                        kind != ChainKind::XYChain,
+                    )
+                } else {
+                    (false, false)
+                };
+                if strong {
+                    self.strong[a].push(b);
+                    self.strong[b].push(a);
+                }
+                if weak {
+                    self.weak[a].push(b);
+                    self.weak[b].push(a);
+                }
+            }
+        }
+    }
+}
+
+fn find_chain(sudoku: &Sudoku, kind: ChainKind, max_length: usize) -> Option<Step> {
+    let candidates = sudoku.get_candidates();
+    let graph = Graph::new(&candidates, kind);
+    let count = graph.nodes.len();
+    for start in 0..count {
+        if graph.strong[start].is_empty() {
+            continue;
+        }
+        // Breadth first over (node, arrived through a strong link) so every chain is the
+        // shortest one reaching its end
+        let mut parent: Vec<Option<usize>> = vec![None; count * 2];
+        let mut seen = vec![false; count * 2];
+        let mut queue = std::collections::VecDeque::new();
+        seen[start * 2] = true;
+        queue.push_back((start * 2, 1));
+        while let Some((state, length)) = queue.pop_front() {
+            let (node, arrived_strong) = (state / 2, state % 2 == 1);
+            if arrived_strong && length >= 4 {
+                let path = path_to(&parent, state);
+                if valid_path(&graph, &path)
+                    && let Some(step) = chain_step(&graph, &candidates, kind, &path)
+                {
+                    return Some(step);
+                }
+            }
+            if length == max_length {
+                continue;
+            }
+            let next = if arrived_strong {
+                &graph.weak[node]
+            } else {
+                &graph.strong[node]
+            };
+            for &n in next {
+                let next_state = n * 2 + usize::from(!arrived_strong);
+                if !seen[next_state] {
+                    seen[next_state] = true;
+                    parent[next_state] = Some(state);
+                    queue.push_back((next_state, length + 1));
+                }
+            }
+        }
+    }
+    None
+}
+
+fn path_to(parent: &[Option<usize>], end: usize) -> Vec<usize> {
+    let mut path = vec![end / 2];
+    let mut state = end;
+    while let Some(prev) = parent[state] {
+        path.push(prev / 2);
+        state = prev;
+    }
+    path.reverse();
+    path
+}
+
+/// Nodes may not repeat, and a cell may only come back right after itself (a bivalue link)
+/// or as the two ends of the chain
+fn valid_path(graph: &Graph, path: &[usize]) -> bool {
+    for i in 0..path.len() {
+        for j in i + 1..path.len() {
+            let (a, b) = (&graph.nodes[path[i]], &graph.nodes[path[j]]);
+            let overlap = a.cells.iter().any(|c| b.cells.contains(c));
+            if !overlap {
+                continue;
+            }
+            let ends = i == 0 && j == path.len() - 1 && a.digit != b.digit;
+            if a.digit == b.digit || (j != i + 1 && !ends) {
+                return false;
+            }
+        }
+    }
+    true
+}
+
+fn chain_step(
+    graph: &Graph,
+    candidates: &[[u16; 9]; 9],
+    kind: ChainKind,
+    path: &[usize],
+) -> Option<Step> {
+    let nodes: Vec<&Node> = path.iter().map(|&i| &graph.nodes[i]).collect();
+    let (first, last) = (nodes[0], nodes[nodes.len() - 1]);
+    let has = |(r, c): Cell, num: usize| candidates[r][c] & bit(num) != 0;
+    let in_chain = |cell: &Cell, num: usize| {
+        nodes
+            .iter()
+            .any(|n| n.digit == num && n.cells.contains(cell))
+    };
+    let mut eliminations: Vec<(Cell, usize)> = vec![];
+    let is_loop = path.len() >= 4 && graph.weak[path[path.len() - 1]].contains(&path[0]);
+    if is_loop {
+        // Closing the loop turns every weak link strong as well
+        let mut pairs: Vec<(&Node, &Node)> = (1..nodes.len() - 1)
+            .step_by(2)
+            .map(|i| (nodes[i], nodes[i + 1]))
+            .collect();
+        pairs.push((last, first));
+        for (a, b) in pairs {
+            if a.digit == b.digit {
+                let all: Vec<Cell> = a.cells.iter().chain(&b.cells).copied().collect();
+                for cell in seen_by_all(&all) {
+                    if has(cell, a.digit) && !in_chain(&cell, a.digit) {
+                        eliminations.push((cell, a.digit));
+                    }
+                }
+            } else {
+                let cell = a.cells[0];
+                let keep = bit(a.digit) | bit(b.digit);
+                for num in digits(candidates[cell.0][cell.1] & !keep) {
+                    eliminations.push((cell, num));
+                }
+            }
+        }
+    } else if first.digit == last.digit {
+        let all: Vec<Cell> = first.cells.iter().chain(&last.cells).copied().collect();
+        for cell in seen_by_all(&all) {
+            if has(cell, first.digit) {
+                eliminations.push((cell, first.digit));
+            }
+        }
+    } else if !first.is_group() && !last.is_group() {
+        let (a, b) = (first.cells[0], last.cells[0]);
+        if a == b {
+            let keep = bit(first.digit) | bit(last.digit);
+            for num in digits(candidates[a.0][a.1] & !keep) {
+                eliminations.push((a, num));
+            }
+        } else if sees(a, b) {
+            if has(a, last.digit) {
+                eliminations.push((a, last.digit));
+            }
+            if has(b, first.digit) {
+                eliminations.push((b, first.digit));
+            }
+        }
+    }
+    eliminations.sort();
+    eliminations.dedup();
+    if eliminations.is_empty() {
+        return None;
+    }
+    let technique = match kind {
+        ChainKind::XCycle => Technique::XCycle,
+        ChainKind::XYChain => Technique::XYChain,
+        ChainKind::Aic if nodes.iter().any(|n| n.is_group()) => Technique::GroupedAic,
+        ChainKind::Aic => Technique::Aic,
+    };
+    let nodes: Vec<Node> = nodes.into_iter().cloned().collect();
+    let eureka = eureka(&nodes, is_loop, &eliminations);
+    Some(Step {
+        technique,
+        placements: vec![],
+        highlights: nodes
+            .iter()
+            .flat_map(|n| n.cells.iter().map(|&cell| (cell, bit(n.digit))))
+            .collect(),
+        explanation: format!("{}: {}", technique.name(), eureka),
+        eliminations,
+        detail: Detail::Chain {
+            nodes,
+            is_loop,
+            eureka,
+        },
+    })
+}
+
+/// Writes the chain in Eureka notation, e.g. `(1=2)r1c1-(2)r1c6=(2)r4c6 => r4c1<>2`.
+/// Consecutive nodes in one cell share their cell name.
+fn eureka(nodes: &[Node], is_loop: bool, eliminations: &[(Cell, usize)]) -> String {
+    let mut chain = String::new();
+    let mut i = 0;
+    while i < nodes.len() {
+        if i > 0 {
+            // Links alternate strong, weak, strong starting from the first node
+            chain.push(if i % 2 == 1 { '=' } else { '-' });
+        }
+        let node = &nodes[i];
+        let next = nodes.get(i + 1).filter(|n| n.cells == node.cells);
+        if let Some(next) = next {
+            let link = if i % 2 == 0 { '=' } else { '-' };
+            chain.push_str(&format!(
+                "({}{}{}){}",
+                node.digit,
+                link,
+                next.digit,
+                node.cells_name()
+            ));
+            i += 2;
+        } else {
+            chain.push_str(&format!("({}){}", node.digit, node.cells_name()));
+            i += 1;
+        }
+    }
+    if is_loop {
+        chain.push_str(&format!("-({}){}", nodes[0].digit, nodes[0].cells_name()));
+    }
+    let mut removed: Vec<String> = vec![];
+    for num in 1..=9 {
+        let cells: Vec<String> = eliminations
+            .iter()
+            .filter(|e| e.1 == num)
+            .map(|&(cell, _)| cell_name(cell))
+            .collect();
+        if !cells.is_empty() {
+            removed.push(format!("{}<>{}", cells.join(","), num));
+        }
+    }
+    format!("{} => {}", chain, removed.join(", "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::strategies::test_util::{keep, restrict};
+
+    #[test]
+    fn test_x_cycle() {
+        let mut sudoku = Sudoku::new();
+        restrict(&mut sudoku, House::Row(0), 1, &[(0, 0), (0, 4)]);
+        restrict(&mut sudoku, House::Row(6), 1, &[(6, 0), (6, 5)]);
+        let step = x_cycle(&sudoku, MAX_CHAIN_LENGTH).unwrap();
+        assert_eq!(step.technique, Technique::XCycle);
+        assert_eq!(
+            step.eliminations,
+            vec![((1, 5), 1), ((2, 5), 1), ((7, 4), 1), ((8, 4), 1)]
+        );
+        assert_eq!(
+            step.explanation,
+            "X-Cycle: (1)r1c5=(1)r1c1-(1)r7c1=(1)r7c6 => r2c6,r3c6,r8c5,r9c5<>1"
+        );
+    }
+    #[test]
+    fn test_xy_chain() {
+        let mut sudoku = Sudoku::new();
+        keep(&mut sudoku, (0, 0), &[1, 2]);
+        keep(&mut sudoku, (0, 5), &[2, 3]);
+        keep(&mut sudoku, (4, 5), &[3, 4]);
+        keep(&mut sudoku, (4, 2), &[4, 1]);
+        let step = xy_chain(&sudoku, MAX_CHAIN_LENGTH).unwrap();
+        assert_eq!(step.technique, Technique::XYChain);
+        // Both ends are 1, in box 1 and row 5
+        assert!(step.eliminations.contains(&((4, 0), 1)));
+        assert!(matches!(
+            step.detail,
+            Detail::Chain { ref eureka, .. } if eureka.starts_with("(1=2)r1c1-(2=3)r1c6-(3=4)r5c6-(4=1)r5c3")
+        ));
+    }
+    #[test]
+    fn test_max_length() {
+        let mut sudoku = Sudoku::new();
+        keep(&mut sudoku, (0, 0), &[1, 2]);
+        keep(&mut sudoku, (0, 5), &[2, 3]);
+        keep(&mut sudoku, (4, 5), &[3, 4]);
+        keep(&mut sudoku, (4, 2), &[4, 1]);
+        assert!(xy_chain(&sudoku, 6).is_none());
+        assert!(xy_chain(&sudoku, 8).is_some());
+    }
+    #[test]
+    fn test_grouped_node_name() {
+        let node = Node {
+            digit: 5,
+            cells: vec![(0, 1), (0, 2)],
+        };
+        assert_eq!(node.cells_name(), "r1c23");
+    }
+}
@@ -0,0 +1,246 @@
+use super::{Cell, Detail, Step, Technique, cell_name, conjugate_pairs, sees};
+use crate::sudoku::{Sudoku, bit};
+
+/// Colors each chain of conjugate pairs of a digit with two alternating colors, one of
+/// which must be entirely true.
+/// A color wrap (two cells of one color seeing each other) removes that whole color, a
+/// color trap removes the digit from cells that see both colors.
+pub fn simple_coloring(sudoku: &Sudoku) -> Option<Step> {
+    let candidates = sudoku.get_candidates();
+    for num in 1..=9 {
+        for cluster in clusters(&candidates, num) {
+            for color in 0..2 {
+                let wrapped = cluster[color]
+                    .iter()
+                    .any(|&a| cluster[color].iter().any(|&b| sees(a, b)));
+                if wrapped {
+                    let eliminations = cluster[color].iter().map(|&cell| (cell, num)).collect();
+                    let explanation = format!(
+                        "color wrap on {}: two cells of one color see each other",
+                        num
+                    );
+                    return Some(coloring_step(
+                        Technique::SimpleColoring,
+                        num,
+                        vec![cluster],
+                        eliminations,
+                        explanation,
+                    ));
+                }
+            }
+            let eliminations = trapped(&candidates, num, &[&cluster], &cluster[0], &cluster[1]);
+            if !eliminations.is_empty() {
+                let explanation = format!("color trap on {}: the cells see both colors", num);
+                return Some(coloring_step(
+                    Technique::SimpleColoring,
+                    num,
+                    vec![cluster],
+                    eliminations,
+                    explanation,
+                ));
+            }
+        }
+    }
+    None
+}
+
+/// Compares two clusters of the same digit. When a color of one cluster sees a color of
+/// the other they cannot both be true, so one of their opposite colors is and the digit
+/// goes from cells that see both opposites. A color that sees both colors of the other
+/// cluster is false outright.
+pub fn multi_coloring(sudoku: &Sudoku) -> Option<Step> {
+    let candidates = sudoku.get_candidates();
+    for num in 1..=9 {
+        let clusters = clusters(&candidates, num);
+        for (i, a) in clusters.iter().enumerate() {
+            for (j, b) in clusters.iter().enumerate() {
+                if i == j {
+                    continue;
+                }
+                let touches =
+                    |x: &[Cell], y: &[Cell]| x.iter().any(|&p| y.iter().any(|&q| sees(p, q)));
+                for ca in 0..2 {
+                    if touches(&a[ca], &b[0]) && touches(&a[ca], &b[1]) {
+                        let eliminations = a[ca].iter().map(|&cell| (cell, num)).collect();
+                        let explanation =
+                            format!("one color of {} sees both colors of another cluster", num);
+                        return Some(coloring_step(
+                            Technique::MultiColoring,
+                            num,
+                            vec![a.clone(), b.clone()],
+                            eliminations,
+                            explanation,
+                        ));
+                    }
+                    // Each pair of clusters is visited in both orders, so only look at
+                    // the first ordering for the shared trap
+                    if i > j {
+                        continue;
+                    }
+                    for cb in 0..2 {
+                        if !touches(&a[ca], &b[cb]) {
+                            continue;
+                        }
+                        let eliminations =
+                            trapped(&candidates, num, &[a, b], &a[1 - ca], &b[1 - cb]);
+                        if eliminations.is_empty() {
+                            continue;
+                        }
+                        let explanation = format!(
+                            "two colors of {} see each other, so one of their opposites is true",
+                            num
+                        );
+                        return Some(coloring_step(
+                            Technique::MultiColoring,
+                            num,
+                            vec![a.clone(), b.clone()],
+                            eliminations,
+                            explanation,
+                        ));
+                    }
+                }
+            }
+        }
+    }
+    None
+}
+
+/// Connected groups of conjugate pairs for a digit, each split into two colors
+fn clusters(candidates: &[[u16; 9]; 9], num: usize) -> Vec<[Vec<Cell>; 2]> {
+    let pairs = conjugate_pairs(candidates, num);
+    let mut color: [[Option<usize>; 9]; 9] = [[None; 9]; 9];
+    let mut clusters = vec![];
+    for &(_, start, _) in &pairs {
+        if color[start.0][start.1].is_some() {
+            continue;
+        }
+        let mut cluster: [Vec<Cell>; 2] = [vec![], vec![]];
+        let mut stack = vec![(start, 0)];
+        color[start.0][start.1] = Some(0);
+        while let Some((cell, c)) = stack.pop() {
+            cluster[c].push(cell);
+            for &(_, a, b) in &pairs {
+                let next = if a == cell {
+                    b
+                } else if b == cell {
+                    a
+                } else {
+                    continue;
+                };
+                if color[next.0][next.1].is_none() {
+                    color[next.0][next.1] = Some(1 - c);
+                    stack.push((next, 1 - c));
+                }
+            }
+        }
+        cluster[0].sort();
+        cluster[1].sort();
+        clusters.push(cluster);
+    }
+    clusters
+}
+
+/// Candidates outside the clusters that see a cell of both colors
+fn trapped(
+    candidates: &[[u16; 9]; 9],
+    num: usize,
+    clusters: &[&[Vec<Cell>; 2]],
+    first: &[Cell],
+    second: &[Cell],
+) -> Vec<(Cell, usize)> {
+    (0..81)
+        .map(|i| (i / 9, i % 9))
+        .filter(|&(r, c)| candidates[r][c] & bit(num) != 0)
+        .filter(|cell| {
+            !clusters
+                .iter()
+                .any(|cl| cl[0].contains(cell) || cl[1].contains(cell))
+        })
+        .filter(|&cell| first.iter().any(|&x| sees(cell, x)))
+        .filter(|&cell| second.iter().any(|&y| sees(cell, y)))
+        .map(|cell| (cell, num))
+        .collect()
+}
+
+fn coloring_step(
+    technique: Technique,
+    num: usize,
+    clusters: Vec<[Vec<Cell>; 2]>,
+    eliminations: Vec<(Cell, usize)>,
+    explanation: String,
+) -> Step {
+    Step {
+        technique,
+        placements: vec![],
+        highlights: clusters
+            .iter()
+            .flat_map(|cluster| cluster.iter().flatten())
+            .map(|&cell| (cell, bit(num)))
+            .collect(),
+        explanation: format!(
+            "{}: {}, removing it from {}",
+            technique.name(),
+            explanation,
+            eliminations
+                .iter()
+                .map(|&(cell, _)| cell_name(cell))
+                .collect::<Vec<_>>()
+                .join(",")
+        ),
+        eliminations,
+        detail: Detail::Coloring {
+            digit: num,
+            clusters,
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::strategies::test_util::restrict;
+    use crate::sudoku::House;
+
+    #[test]
+    fn test_clusters() {
+        let mut sudoku = Sudoku::new();
+        restrict(&mut sudoku, House::Row(0), 2, &[(0, 0), (0, 4)]);
+        restrict(&mut sudoku, House::Column(4), 2, &[(0, 4), (6, 4)]);
+        let clusters = clusters(&sudoku.get_candidates(), 2);
+        assert_eq!(clusters, vec![[vec![(0, 0), (6, 4)], vec![(0, 4)]]]);
+    }
+    #[test]
+    fn test_color_trap() {
+        let mut sudoku = Sudoku::new();
+        restrict(&mut sudoku, House::Row(0), 2, &[(0, 0), (0, 4)]);
+        restrict(&mut sudoku, House::Column(4), 2, &[(0, 4), (6, 4)]);
+        restrict(&mut sudoku, House::Row(6), 2, &[(6, 4), (6, 8)]);
+        restrict(&mut sudoku, House::Column(8), 2, &[(6, 8), (2, 8)]);
+        let step = simple_coloring(&sudoku).unwrap();
+        assert_eq!(
+            step.detail,
+            Detail::Coloring {
+                digit: 2,
+                clusters: vec![[vec![(0, 0), (2, 8), (6, 4)], vec![(0, 4), (6, 8)]]],
+            }
+        );
+        // Only the cells of box 2 on row 3 see r1c5 and r3c9 at once
+        assert_eq!(step.eliminations, vec![((2, 3), 2), ((2, 5), 2)]);
+        assert!(step.explanation.contains("color trap"));
+    }
+    #[test]
+    fn test_color_wrap() {
+        let mut sudoku = Sudoku::new();
+        restrict(&mut sudoku, House::Row(0), 5, &[(0, 0), (0, 4)]);
+        restrict(&mut sudoku, House::Column(4), 5, &[(0, 4), (6, 4)]);
+        restrict(&mut sudoku, House::Row(6), 5, &[(6, 4), (6, 1)]);
+        restrict(&mut sudoku, House::Column(1), 5, &[(6, 1), (1, 1)]);
+        // r1c1 and r2c2 end up on the same color inside box 1
+        let step = simple_coloring(&sudoku).unwrap();
+        assert_eq!(
+            step.eliminations,
+            vec![((0, 0), 5), ((1, 1), 5), ((6, 4), 5)]
+        );
+        assert!(step.explanation.contains("color wrap"));
+    }
+}
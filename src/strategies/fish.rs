@@ -0,0 +1,330 @@
+use super::{Cell, Detail, Step, Technique, cell_name, combinations, houses_name};
+use crate::sudoku::{House, Sudoku, bit, box_of};
+
+/// `size` rows (or columns) whose candidates for a digit all fall into `size` columns (or
+/// rows). The digit can be removed from the rest of those cover lines.
+/// A size of 2 is an X-Wing, 3 a Swordfish and 4 a Jellyfish.
+pub fn basic_fish(sudoku: &Sudoku, size: usize) -> Option<Step> {
+    let candidates = sudoku.get_candidates();
+    for num in 1..=9 {
+        for by_row in [true, false] {
+            let lines = Lines::new(&candidates, num, by_row);
+            let base_lines: Vec<usize> = (0..9)
+                .filter(|&i| (2..=size as u32).contains(&lines.positions[i].count_ones()))
+                .collect();
+            for base in combinations(&base_lines, size) {
+                let cover = base.iter().fold(0u16, |m, &i| m | lines.positions[i]);
+                if cover.count_ones() as usize != size {
+                    continue;
+                }
+                let eliminations: Vec<(Cell, usize)> = (0..9)
+                    .filter(|i| !base.contains(i))
+                    .flat_map(|i| {
+                        (0..9)
+                            .filter(move |&j| lines.positions[i] & cover & (1 << j) != 0)
+                            .map(move |j| (lines.cell(i, j), num))
+                    })
+                    .collect();
+                if eliminations.is_empty() {
+                    continue;
+                }
+                let technique = match size {
+                    2 => Technique::XWing,
+                    3 => Technique::Swordfish,
+                    _ => Technique::Jellyfish,
+                };
+                return Some(fish_step(
+                    technique,
+                    &lines,
+                    num,
+                    &base,
+                    cover,
+                    vec![],
+                    eliminations,
+                ));
+            }
+        }
+    }
+    None
+}
+
+/// A fish whose base lines also hold a few extra candidates, the fins, all inside one box.
+/// Either the fish is real or one of the fins is true, so only candidates that are both in
+/// a cover line and in the fin box can be removed. When a base line is left with a single
+/// candidate inside the cover lines the fish is called sashimi.
+pub fn finned_fish(sudoku: &Sudoku, size: usize) -> Option<Step> {
+    let candidates = sudoku.get_candidates();
+    for num in 1..=9 {
+        for by_row in [true, false] {
+            let lines = Lines::new(&candidates, num, by_row);
+            // Two fins per base line is already more than a single box can hold alongside
+            // the body of the fish
+            let base_lines: Vec<usize> = (0..9)
+                .filter(|&i| (1..=size as u32 + 2).contains(&lines.positions[i].count_ones()))
+                .collect();
+            for base in combinations(&base_lines, size) {
+                let union = base.iter().fold(0u16, |m, &i| m | lines.positions[i]);
+                let indices: Vec<usize> = (0..9).filter(|j| union & (1 << j) != 0).collect();
+                if indices.len() <= size {
+                    continue;
+                }
+                for cover in combinations(&indices, size) {
+                    let cover = cover.iter().fold(0u16, |m, &j| m | (1 << j));
+                    if let Some(step) = finned_step(&lines, num, &base, cover) {
+                        return Some(step);
+                    }
+                }
+            }
+        }
+    }
+    None
+}
+
+fn finned_step(lines: &Lines, num: usize, base: &[usize], cover: u16) -> Option<Step> {
+    let mut fins = vec![];
+    for &i in base {
+        let body = lines.positions[i] & cover;
+        if body == 0 {
+            return None;
+        }
+        fins.extend(
+            (0..9)
+                .filter(|&j| lines.positions[i] & !cover & (1 << j) != 0)
+                .map(|j| lines.cell(i, j)),
+        );
+    }
+    let fin_box = box_of(fins[0].0, fins[0].1);
+    if fins.iter().any(|&(r, c)| box_of(r, c) != fin_box) {
+        return None;
+    }
+    let eliminations: Vec<(Cell, usize)> = (0..9)
+        .filter(|i| !base.contains(i))
+        .flat_map(|i| {
+            (0..9)
+                .filter(move |&j| lines.positions[i] & cover & (1 << j) != 0)
+                .map(move |j| lines.cell(i, j))
+        })
+        .filter(|&(r, c)| box_of(r, c) == fin_box)
+        .map(|cell| (cell, num))
+        .collect();
+    if eliminations.is_empty() {
+        return None;
+    }
+    let sashimi = base
+        .iter()
+        .any(|&i| (lines.positions[i] & cover).count_ones() == 1);
+    let technique = match (base.len(), sashimi) {
+        (2, false) => Technique::FinnedXWing,
+        (3, false) => Technique::FinnedSwordfish,
+        (_, false) => Technique::FinnedJellyfish,
+        (2, true) => Technique::SashimiXWing,
+        (3, true) => Technique::SashimiSwordfish,
+        (_, true) => Technique::SashimiJellyfish,
+    };
+    Some(fish_step(
+        technique,
+        lines,
+        num,
+        base,
+        cover,
+        fins,
+        eliminations,
+    ))
+}
+
+/// Candidates of one digit seen line by line, either along the rows or along the columns
+#[derive(Clone, Copy)]
+struct Lines {
+    by_row: bool,
+    /// Bit `j` of `positions[i]` is set when the digit can go at index `j` of line `i`
+    positions: [u16; 9],
+}
+
+impl Lines {
+    fn new(candidates: &[[u16; 9]; 9], num: usize, by_row: bool) -> Self {
+        let mut positions = [0; 9];
+        for (i, line) in positions.iter_mut().enumerate() {
+            for j in 0..9 {
+                let (r, c) = if by_row { (i, j) } else { (j, i) };
+                if candidates[r][c] & bit(num) != 0 {
+                    *line |= 1 << j;
+                }
+            }
+        }
+        Self { by_row, positions }
+    }
+    fn cell(&self, line: usize, index: usize) -> Cell {
+        if self.by_row {
+            (line, index)
+        } else {
+            (index, line)
+        }
+    }
+    fn base_house(&self, line: usize) -> House {
+        if self.by_row {
+            House::Row(line)
+        } else {
+            House::Column(line)
+        }
+    }
+    fn cover_house(&self, index: usize) -> House {
+        if self.by_row {
+            House::Column(index)
+        } else {
+            House::Row(index)
+        }
+    }
+}
+
+fn fish_step(
+    technique: Technique,
+    lines: &Lines,
+    num: usize,
+    base: &[usize],
+    cover: u16,
+    fins: Vec<Cell>,
+    eliminations: Vec<(Cell, usize)>,
+) -> Step {
+    let base_houses: Vec<House> = base.iter().map(|&i| lines.base_house(i)).collect();
+    let cover_houses: Vec<House> = (0..9)
+        .filter(|j| cover & (1 << j) != 0)
+        .map(|j| lines.cover_house(j))
+        .collect();
+    Step {
+        technique,
+        placements: vec![],
+        eliminations,
+        highlights: base
+            .iter()
+            .flat_map(|&i| {
+                (0..9)
+                    .filter(move |&j| lines.positions[i] & (1 << j) != 0)
+                    .map(move |j| (lines.cell(i, j), bit(num)))
+            })
+            .collect(),
+        explanation: if fins.is_empty() {
+            format!(
+                "{} on {}: {} is covered by {}",
+                technique.name(),
+                num,
+                houses_name(&base_houses),
+                houses_name(&cover_houses)
+            )
+        } else {
+            format!(
+                "{} on {}: {} is covered by {} with fins at {}",
+                technique.name(),
+                num,
+                houses_name(&base_houses),
+                houses_name(&cover_houses),
+                fins.iter()
+                    .map(|&fin| cell_name(fin))
+                    .collect::<Vec<_>>()
+                    .join(",")
+            )
+        },
+        detail: Detail::Fish {
+            digit: num,
+            base: base_houses,
+            cover: cover_houses,
+            fins,
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Leaves `num` only in the given columns of a row
+    fn restrict_row(sudoku: &mut Sudoku, row: usize, num: usize, cols: &[usize]) {
+        for col in 0..9 {
+            if !cols.contains(&col) {
+                sudoku.eliminate_candidate(row, col, num);
+            }
+        }
+    }
+
+    #[test]
+    fn test_x_wing() {
+        let mut sudoku = Sudoku::new();
+        restrict_row(&mut sudoku, 1, 4, &[2, 6]);
+        restrict_row(&mut sudoku, 7, 4, &[2, 6]);
+        let step = basic_fish(&sudoku, 2).unwrap();
+        assert_eq!(step.technique, Technique::XWing);
+        assert_eq!(
+            step.detail,
+            Detail::Fish {
+                digit: 4,
+                base: vec![House::Row(1), House::Row(7)],
+                cover: vec![House::Column(2), House::Column(6)],
+                fins: vec![],
+            }
+        );
+        assert_eq!(step.eliminations.len(), 14);
+        assert!(step.eliminations.contains(&((0, 2), 4)));
+        assert!(!step.eliminations.contains(&((1, 2), 4)));
+        assert_eq!(
+            step.explanation,
+            "X-Wing on 4: row 2, row 8 is covered by column 3, column 7"
+        );
+    }
+    #[test]
+    fn test_column_swordfish() {
+        let mut sudoku = Sudoku::new();
+        for (col, rows) in [(0, [0, 4]), (3, [4, 8]), (8, [0, 8])] {
+            for row in 0..9 {
+                if !rows.contains(&row) {
+                    sudoku.eliminate_candidate(row, col, 9);
+                }
+            }
+        }
+        assert!(basic_fish(&sudoku, 2).is_none());
+        let step = basic_fish(&sudoku, 3).unwrap();
+        assert_eq!(step.technique, Technique::Swordfish);
+        assert!(matches!(
+            step.detail,
+            Detail::Fish { ref base, .. } if base == &[House::Column(0), House::Column(3), House::Column(8)]
+        ));
+        assert_eq!(step.eliminations.len(), 18);
+    }
+    #[test]
+    fn test_finned_x_wing() {
+        let mut sudoku = Sudoku::new();
+        restrict_row(&mut sudoku, 1, 4, &[2, 6]);
+        // r8c8 is a fin in box 9 next to the r8c7 corner
+        restrict_row(&mut sudoku, 7, 4, &[2, 6, 7]);
+        assert!(basic_fish(&sudoku, 2).is_none());
+        let step = finned_fish(&sudoku, 2).unwrap();
+        assert_eq!(step.technique, Technique::FinnedXWing);
+        assert_eq!(
+            step.detail,
+            Detail::Fish {
+                digit: 4,
+                base: vec![House::Row(1), House::Row(7)],
+                cover: vec![House::Column(2), House::Column(6)],
+                fins: vec![(7, 7)],
+            }
+        );
+        // Only the cover cells in box 9 see the fin
+        assert_eq!(step.eliminations, vec![((6, 6), 4), ((8, 6), 4)]);
+        assert_eq!(
+            step.explanation,
+            "Finned X-Wing on 4: row 2, row 8 is covered by column 3, column 7 with fins at r8c8"
+        );
+    }
+    #[test]
+    fn test_sashimi_x_wing() {
+        let mut sudoku = Sudoku::new();
+        restrict_row(&mut sudoku, 1, 4, &[2, 6]);
+        // Row 8 lost its r8c7 corner, leaving only fins in box 9
+        restrict_row(&mut sudoku, 7, 4, &[2, 7, 8]);
+        let step = finned_fish(&sudoku, 2).unwrap();
+        assert_eq!(step.technique, Technique::SashimiXWing);
+        assert!(matches!(
+            step.detail,
+            Detail::Fish { ref fins, .. } if fins == &[(7, 7), (7, 8)]
+        ));
+        assert_eq!(step.eliminations, vec![((6, 6), 4), ((8, 6), 4)]);
+    }
+}
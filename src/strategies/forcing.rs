@@ -0,0 +1,432 @@
+use std::collections::HashSet;
+use std::fmt;
+
+use super::{Cell, Detail, Step, Technique, cell_name};
+use crate::sudoku::{House, Sudoku, bit, digits, peers};
+
+/// Default cap on how many implications deep an assumption is followed
+pub const MAX_FORCING_DEPTH: usize = 12;
+
+/// Cells with more candidates than this are not tried as the start of a cell forcing chain,
+/// and houses with more places for a digit are not tried for a unit forcing chain
+const MAX_BRANCHES: usize = 3;
+
+/// A single fact derived while following an assumption
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Implication {
+    Place(Cell, usize),
+    Remove(Cell, usize),
+}
+
+impl fmt::Display for Implication {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Implication::Place(cell, num) => write!(f, "{}={}", cell_name(cell), num),
+            Implication::Remove(cell, num) => write!(f, "{}<>{}", cell_name(cell), num),
+        }
+    }
+}
+
+/// An assumed placement and the implications from it to the fact it proved, or to the
+/// contradiction it ran into
+#[derive(Debug, Clone, PartialEq)]
+pub struct Branch {
+    pub assumption: (Cell, usize),
+    pub trace: Vec<Implication>,
+    pub contradiction: bool,
+}
+
+impl Branch {
+    fn trace_name(&self) -> String {
+        self.trace
+            .iter()
+            .map(|implication| implication.to_string())
+            .collect::<Vec<_>>()
+            .join(" -> ")
+    }
+}
+
+struct Fact {
+    implication: Implication,
+    cause: Option<usize>,
+    depth: usize,
+}
+
+/// Breadth first consequences of one placement through singles. With `digit` set only that
+/// digit is followed, which is what Nishio does.
+struct Propagation {
+    board: [[usize; 9]; 9],
+    candidates: [[u16; 9]; 9],
+    digit: Option<usize>,
+    facts: Vec<Fact>,
+    contradiction: Option<usize>,
+}
+
+impl Propagation {
+    fn run(
+        sudoku: &Sudoku,
+        (cell, num): (Cell, usize),
+        digit: Option<usize>,
+        max_depth: usize,
+    ) -> Self {
+        let mut propagation = Propagation {
+            board: sudoku.get_board(),
+            candidates: sudoku.get_candidates(),
+            digit,
+            facts: vec![],
+            contradiction: None,
+        };
+        propagation.place(cell, num, None);
+        let mut next = 0;
+        while next < propagation.facts.len() && propagation.contradiction.is_none() {
+            if propagation.facts[next].depth < max_depth {
+                propagation.expand(next);
+            }
+            next += 1;
+        }
+        propagation
+    }
+
+    fn push(&mut self, implication: Implication, cause: Option<usize>) -> usize {
+        let depth = cause.map_or(0, |i| self.facts[i].depth + 1);
+        self.facts.push(Fact {
+            implication,
+            cause,
+            depth,
+        });
+        self.facts.len() - 1
+    }
+
+    fn place(&mut self, (row, col): Cell, num: usize, cause: Option<usize>) {
+        if self.board[row][col] == num || self.contradiction.is_some() {
+            return;
+        }
+        let index = self.push(Implication::Place((row, col), num), cause);
+        if self.board[row][col] != 0 || self.candidates[row][col] & bit(num) == 0 {
+            self.contradiction = Some(index);
+            return;
+        }
+        self.board[row][col] = num;
+    }
+
+    fn remove(&mut self, (row, col): Cell, num: usize, cause: usize) {
+        if self.candidates[row][col] & bit(num) == 0 || self.contradiction.is_some() {
+            return;
+        }
+        self.candidates[row][col] &= !bit(num);
+        let index = self.push(Implication::Remove((row, col), num), Some(cause));
+        if self.board[row][col] == num {
+            self.contradiction = Some(index);
+        }
+    }
+
+    fn expand(&mut self, index: usize) {
+        match self.facts[index].implication {
+            Implication::Place((row, col), num) => {
+                if self.digit.is_none() {
+                    for other in digits(self.candidates[row][col] & !bit(num)) {
+                        self.remove((row, col), other, index);
+                    }
+                }
+                for (r, c) in peers(row, col) {
+                    self.remove((r, c), num, index);
+                }
+            }
+            Implication::Remove((row, col), num) => {
+                if self.digit.is_none() && self.board[row][col] == 0 {
+                    let mask = self.candidates[row][col];
+                    if mask == 0 {
+                        self.contradiction = Some(index);
+                        return;
+                    }
+                    if mask.count_ones() == 1 {
+                        self.place((row, col), digits(mask).next().unwrap(), Some(index));
+                    }
+                }
+                for house in House::all().filter(|house| house.contains((row, col))) {
+                    let cells = house.cells();
+                    if cells.iter().any(|&(r, c)| self.board[r][c] == num) {
+                        continue;
+                    }
+                    let holders: Vec<Cell> = cells
+                        .into_iter()
+                        .filter(|&(r, c)| {
+                            self.board[r][c] == 0 && self.candidates[r][c] & bit(num) != 0
+                        })
+                        .collect();
+                    match holders[..] {
+                        [] => {
+                            self.contradiction = Some(index);
+                            return;
+                        }
+                        [cell] => self.place(cell, num, Some(index)),
+                        _ => {}
+                    }
+                }
+            }
+        }
+    }
+
+    /// Implications from the assumption down to the fact at `index`
+    fn trace(&self, index: usize) -> Vec<Implication> {
+        let mut trace = vec![];
+        let mut next = Some(index);
+        while let Some(i) = next {
+            trace.push(self.facts[i].implication);
+            next = self.facts[i].cause;
+        }
+        trace.reverse();
+        trace
+    }
+
+    fn branch(&self, assumption: (Cell, usize), index: usize) -> Branch {
+        Branch {
+            assumption,
+            trace: self.trace(index),
+            contradiction: self.contradiction.is_some(),
+        }
+    }
+}
+
+/// Assumes each place for one digit in turn, following only that digit. A placement that
+/// leaves a house without the digit, or places it twice, is false.
+pub fn digit_nishio(sudoku: &Sudoku, max_depth: usize) -> Option<Step> {
+    let candidates = sudoku.get_candidates();
+    for num in 1..=9 {
+        for cell in (0..81).map(|i| (i / 9, i % 9)) {
+            if candidates[cell.0][cell.1] & bit(num) == 0 {
+                continue;
+            }
+            let propagation = Propagation::run(sudoku, (cell, num), Some(num), max_depth);
+            let Some(index) = propagation.contradiction else {
+                continue;
+            };
+            let branch = propagation.branch((cell, num), index);
+            return Some(Step {
+                technique: Technique::DigitNishio,
+                placements: vec![],
+                eliminations: vec![(cell, num)],
+                highlights: vec![(cell, bit(num))],
+                explanation: format!(
+                    "{}: {} is impossible, so {}",
+                    Technique::DigitNishio.name(),
+                    branch.trace_name(),
+                    Implication::Remove(cell, num)
+                ),
+                detail: Detail::Forcing {
+                    branches: vec![branch],
+                },
+            });
+        }
+    }
+    None
+}
+
+/// Assumes every candidate of a cell in turn. Whatever follows from all of them is true.
+pub fn cell_forcing_chain(sudoku: &Sudoku, max_depth: usize) -> Option<Step> {
+    let candidates = sudoku.get_candidates();
+    for (row, col) in (0..81).map(|i| (i / 9, i % 9)) {
+        let mask = candidates[row][col];
+        if mask.count_ones() < 2 || mask.count_ones() as usize > MAX_BRANCHES {
+            continue;
+        }
+        let assumptions: Vec<(Cell, usize)> = digits(mask).map(|num| ((row, col), num)).collect();
+        if let Some(step) = common_conclusion(
+            sudoku,
+            Technique::CellForcingChain,
+            &format!("every candidate of {}", cell_name((row, col))),
+            &assumptions,
+            max_depth,
+        ) {
+            return Some(step);
+        }
+    }
+    None
+}
+
+/// Assumes every place for a digit in a house in turn. Whatever follows from all of them is
+/// true.
+pub fn unit_forcing_chain(sudoku: &Sudoku, max_depth: usize) -> Option<Step> {
+    let candidates = sudoku.get_candidates();
+    for house in House::all() {
+        for num in 1..=9 {
+            let assumptions: Vec<(Cell, usize)> = house
+                .cells()
+                .into_iter()
+                .filter(|&(r, c)| candidates[r][c] & bit(num) != 0)
+                .map(|cell| (cell, num))
+                .collect();
+            if assumptions.len() < 2 || assumptions.len() > MAX_BRANCHES {
+                continue;
+            }
+            if let Some(step) = common_conclusion(
+                sudoku,
+                Technique::UnitForcingChain,
+                &format!("every place for {num} in {house}"),
+                &assumptions,
+                max_depth,
+            ) {
+                return Some(step);
+            }
+        }
+    }
+    None
+}
+
+/// Follows every assumption and looks for a fact that all of them imply, placements first.
+/// Assumptions ending in a contradiction are false, so they are left out.
+fn common_conclusion(
+    sudoku: &Sudoku,
+    technique: Technique,
+    source: &str,
+    assumptions: &[(Cell, usize)],
+    max_depth: usize,
+) -> Option<Step> {
+    let propagations: Vec<(&(Cell, usize), Propagation)> = assumptions
+        .iter()
+        .map(|assumption| {
+            (
+                assumption,
+                Propagation::run(sudoku, *assumption, None, max_depth),
+            )
+        })
+        .filter(|(_, propagation)| propagation.contradiction.is_none())
+        .collect();
+    let (_, first) = propagations.first()?;
+    let sets: Vec<HashSet<Implication>> = propagations
+        .iter()
+        .map(|(_, p)| p.facts.iter().map(|fact| fact.implication).collect())
+        .collect();
+    let common = |implication: &Implication| sets.iter().all(|set| set.contains(implication));
+    let placements = first
+        .facts
+        .iter()
+        .filter(|fact| matches!(fact.implication, Implication::Place(..)));
+    let removals = first
+        .facts
+        .iter()
+        .filter(|fact| matches!(fact.implication, Implication::Remove(..)));
+    let conclusion = placements
+        .chain(removals)
+        .map(|fact| fact.implication)
+        .find(|implication| common(implication))?;
+    let branches: Vec<Branch> = propagations
+        .iter()
+        .map(|(assumption, propagation)| {
+            let index = propagation
+                .facts
+                .iter()
+                .position(|fact| fact.implication == conclusion)
+                .unwrap();
+            propagation.branch(**assumption, index)
+        })
+        .collect();
+    let (placements, eliminations, highlight) = match conclusion {
+        Implication::Place(cell, num) => (vec![(cell, num)], vec![], (cell, bit(num))),
+        Implication::Remove(cell, num) => (vec![], vec![(cell, num)], (cell, bit(num))),
+    };
+    let mut highlights: Vec<(Cell, u16)> = assumptions
+        .iter()
+        .map(|&(cell, num)| (cell, bit(num)))
+        .collect();
+    highlights.push(highlight);
+    Some(Step {
+        technique,
+        placements,
+        eliminations,
+        highlights,
+        explanation: format!(
+            "{}: {} leads to {} ({})",
+            technique.name(),
+            source,
+            conclusion,
+            branches
+                .iter()
+                .map(|branch| branch.trace_name())
+                .collect::<Vec<_>>()
+                .join("; ")
+        ),
+        detail: Detail::Forcing { branches },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::strategies::test_util::{keep, restrict};
+
+    #[test]
+    fn test_propagation_depth() {
+        let mut sudoku = Sudoku::new();
+        keep(&mut sudoku, (0, 0), &[1, 2]);
+        keep(&mut sudoku, (0, 5), &[1, 3]);
+        let shallow = Propagation::run(&sudoku, ((0, 0), 1), None, 1);
+        assert!(
+            !shallow
+                .facts
+                .iter()
+                .any(|fact| fact.implication == Implication::Place((0, 5), 3))
+        );
+        let deep = Propagation::run(&sudoku, ((0, 0), 1), None, 2);
+        let index = deep
+            .facts
+            .iter()
+            .position(|fact| fact.implication == Implication::Place((0, 5), 3))
+            .unwrap();
+        assert_eq!(
+            deep.trace(index),
+            vec![
+                Implication::Place((0, 0), 1),
+                Implication::Remove((0, 5), 1),
+                Implication::Place((0, 5), 3)
+            ]
+        );
+    }
+    #[test]
+    fn test_digit_nishio() {
+        let mut sudoku = Sudoku::new();
+        restrict(&mut sudoku, House::Row(0), 1, &[(0, 0), (0, 1)]);
+        let step = digit_nishio(&sudoku, MAX_FORCING_DEPTH).unwrap();
+        assert_eq!(step.eliminations, vec![((1, 0), 1)]);
+        assert_eq!(
+            step.explanation,
+            "Digit Nishio: r2c1=1 -> r1c1<>1 is impossible, so r2c1<>1"
+        );
+        assert!(digit_nishio(&sudoku, 0).is_none());
+    }
+    #[test]
+    fn test_cell_forcing_chain() {
+        let mut sudoku = Sudoku::new();
+        keep(&mut sudoku, (0, 0), &[1, 2]);
+        keep(&mut sudoku, (0, 5), &[1, 3]);
+        keep(&mut sudoku, (4, 0), &[2, 3]);
+        let step = cell_forcing_chain(&sudoku, MAX_FORCING_DEPTH).unwrap();
+        assert_eq!(step.eliminations, vec![((4, 5), 3)]);
+        let Detail::Forcing { branches } = &step.detail else {
+            panic!("expected forcing detail");
+        };
+        assert_eq!(branches.len(), 2);
+        assert_eq!(
+            branches[0].trace,
+            vec![
+                Implication::Place((0, 0), 1),
+                Implication::Remove((0, 5), 1),
+                Implication::Place((0, 5), 3),
+                Implication::Remove((4, 5), 3)
+            ]
+        );
+    }
+    #[test]
+    fn test_unit_forcing_chain() {
+        let mut sudoku = Sudoku::new();
+        restrict(&mut sudoku, House::Row(0), 1, &[(0, 0), (0, 4)]);
+        keep(&mut sudoku, (0, 0), &[1, 2]);
+        keep(&mut sudoku, (0, 4), &[1, 2]);
+        let step = unit_forcing_chain(&sudoku, MAX_FORCING_DEPTH).unwrap();
+        assert_eq!(step.eliminations, vec![((0, 1), 2)]);
+        assert!(
+            step.explanation
+                .starts_with("Unit Forcing Chain: every place for 1 in row 1 leads to r1c2<>2")
+        );
+    }
+}
@@ -0,0 +1,324 @@
+use super::{Cell, Detail, Step, Technique, cell_name, combinations, digits_name};
+use crate::sudoku::{House, Sudoku, bit, digits};
+
+/// The three cells a box shares with one of its rows or columns
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Intersection {
+    pub block: House,
+    pub line: House,
+}
+
+impl Intersection {
+    /// All 54 box/line intersections
+    pub fn all() -> impl Iterator<Item = Intersection> {
+        (0..9).flat_map(|b| {
+            let rows = (0..3).map(move |i| House::Row(b / 3 * 3 + i));
+            let cols = (0..3).map(move |i| House::Column(b % 3 * 3 + i));
+            rows.chain(cols).map(move |line| Intersection {
+                block: House::Box(b),
+                line,
+            })
+        })
+    }
+    pub fn cells(&self) -> Vec<Cell> {
+        self.block
+            .cells()
+            .into_iter()
+            .filter(|&cell| self.line.contains(cell))
+            .collect()
+    }
+    /// Cells of the box outside the line
+    pub fn box_rest(&self) -> Vec<Cell> {
+        self.block
+            .cells()
+            .into_iter()
+            .filter(|&cell| !self.line.contains(cell))
+            .collect()
+    }
+    /// Cells of the line outside the box
+    pub fn line_rest(&self) -> Vec<Cell> {
+        self.line
+            .cells()
+            .into_iter()
+            .filter(|&cell| !self.block.contains(cell))
+            .collect()
+    }
+}
+
+/// A digit whose candidates in a box all sit on one line can be removed from the rest of
+/// that line.
+pub fn pointing(sudoku: &Sudoku) -> Option<Step> {
+    find_locked(sudoku, true)
+}
+
+/// A digit whose candidates in a line all sit in one box can be removed from the rest of
+/// that box.
+pub fn box_line_reduction(sudoku: &Sudoku) -> Option<Step> {
+    find_locked(sudoku, false)
+}
+
+fn find_locked(sudoku: &Sudoku, pointing: bool) -> Option<Step> {
+    let candidates = sudoku.get_candidates();
+    let has = |(r, c): Cell, num: usize| candidates[r][c] & bit(num) != 0;
+    for intersection in Intersection::all() {
+        let (source, target) = if pointing {
+            (intersection.box_rest(), intersection.line_rest())
+        } else {
+            (intersection.line_rest(), intersection.box_rest())
+        };
+        for num in 1..=9 {
+            let cells: Vec<Cell> = intersection
+                .cells()
+                .into_iter()
+                .filter(|&cell| has(cell, num))
+                .collect();
+            // A lone candidate is a hidden single, leave it to the singles
+            if cells.len() < 2 || source.iter().any(|&cell| has(cell, num)) {
+                continue;
+            }
+            let eliminations: Vec<(Cell, usize)> = target
+                .iter()
+                .filter(|&&cell| has(cell, num))
+                .map(|&cell| (cell, num))
+                .collect();
+            if eliminations.is_empty() {
+                continue;
+            }
+            let (technique, explanation) = if pointing {
+                let technique = if cells.len() == 2 {
+                    Technique::PointingPair
+                } else {
+                    Technique::PointingTriple
+                };
+                (
+                    technique,
+                    format!(
+                        "{} in {} can only go in {}, removing it from the rest of {}",
+                        num, intersection.block, intersection.line, intersection.line
+                    ),
+                )
+            } else {
+                (
+                    Technique::BoxLineReduction,
+                    format!(
+                        "{} in {} can only go in {}, removing it from the rest of {}",
+                        num, intersection.line, intersection.block, intersection.block
+                    ),
+                )
+            };
+            return Some(Step {
+                technique,
+                placements: vec![],
+                eliminations,
+                highlights: cells.iter().map(|&cell| (cell, bit(num))).collect(),
+                detail: Detail::Intersection {
+                    digit: num,
+                    intersection,
+                    cells,
+                },
+                explanation,
+            });
+        }
+    }
+    None
+}
+
+/// Two or three intersection cells holding at least two more digits than cells, plus cells
+/// from the rest of the line and the rest of the box that share no digit with each other.
+/// Together they hold as many digits as cells, so the line part's digits go from the rest of
+/// the line and the box part's digits from the rest of the box. The outside cells may hold
+/// digits the intersection lacks, which covers the extended forms.
+pub fn sue_de_coq(sudoku: &Sudoku) -> Option<Step> {
+    let candidates = sudoku.get_candidates();
+    let mask_of = |cells: &[Cell]| {
+        cells
+            .iter()
+            .fold(0, |mask, &(r, c)| mask | candidates[r][c])
+    };
+    let subsets = |cells: Vec<Cell>| -> Vec<(Vec<Cell>, u16)> {
+        let unsolved: Vec<Cell> = cells
+            .into_iter()
+            .filter(|&(r, c)| candidates[r][c] != 0)
+            .collect();
+        (1..=unsolved.len())
+            .flat_map(|size| combinations(&unsolved, size))
+            .map(|cells| {
+                let mask = mask_of(&cells);
+                (cells, mask)
+            })
+            .collect()
+    };
+    for intersection in Intersection::all() {
+        let unsolved: Vec<Cell> = intersection
+            .cells()
+            .into_iter()
+            .filter(|&(r, c)| candidates[r][c] != 0)
+            .collect();
+        let line_parts = subsets(intersection.line_rest());
+        let box_parts = subsets(intersection.box_rest());
+        for size in 2..=unsolved.len() {
+            for cells in combinations(&unsolved, size) {
+                let mask = mask_of(&cells);
+                if (mask.count_ones() as usize) < size + 2 {
+                    continue;
+                }
+                for (line_cells, line_mask) in &line_parts {
+                    if line_mask & mask == 0 {
+                        continue;
+                    }
+                    for (box_cells, box_mask) in &box_parts {
+                        if box_mask & mask == 0 || box_mask & line_mask != 0 {
+                            continue;
+                        }
+                        let total = cells.len() + line_cells.len() + box_cells.len();
+                        if (mask | line_mask | box_mask).count_ones() as usize != total {
+                            continue;
+                        }
+                        let line_digits = line_mask | (mask & !box_mask);
+                        let box_digits = box_mask | (mask & !line_mask);
+                        let mut eliminations = vec![];
+                        for (house, part, remove) in [
+                            (intersection.line, line_cells, line_digits),
+                            (intersection.block, box_cells, box_digits),
+                        ] {
+                            for cell in house.cells() {
+                                if cells.contains(&cell) || part.contains(&cell) {
+                                    continue;
+                                }
+                                for num in digits(candidates[cell.0][cell.1] & remove) {
+                                    eliminations.push((cell, num));
+                                }
+                            }
+                        }
+                        eliminations.sort();
+                        eliminations.dedup();
+                        if eliminations.is_empty() {
+                            continue;
+                        }
+                        let names = |cells: &[Cell], mask: u16| {
+                            let names: Vec<String> =
+                                cells.iter().map(|&cell| cell_name(cell)).collect();
+                            format!("{} {}", names.join(","), digits_name(mask))
+                        };
+                        return Some(Step {
+                            technique: Technique::SueDeCoq,
+                            placements: vec![],
+                            eliminations,
+                            highlights: cells
+                                .iter()
+                                .chain(line_cells)
+                                .chain(box_cells)
+                                .map(|&(r, c)| ((r, c), candidates[r][c]))
+                                .collect(),
+                            explanation: format!(
+                                "{}: {} with {} in {} and {} in {}",
+                                Technique::SueDeCoq.name(),
+                                names(&cells, mask),
+                                names(line_cells, *line_mask),
+                                intersection.line,
+                                names(box_cells, *box_mask),
+                                intersection.block
+                            ),
+                            detail: Detail::SueDeCoq {
+                                intersection,
+                                cells,
+                                line_cells: line_cells.clone(),
+                                box_cells: box_cells.clone(),
+                            },
+                        });
+                    }
+                }
+            }
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::strategies::test_util::keep;
+
+    #[test]
+    fn test_intersection_cells() {
+        assert_eq!(Intersection::all().count(), 54);
+        let intersection = Intersection {
+            block: House::Box(4),
+            line: House::Column(5),
+        };
+        assert_eq!(intersection.cells(), vec![(3, 5), (4, 5), (5, 5)]);
+        assert_eq!(intersection.box_rest().len(), 6);
+        assert_eq!(intersection.line_rest().len(), 6);
+        assert!(!intersection.line_rest().contains(&(4, 5)));
+    }
+    #[test]
+    fn test_pointing_pair() {
+        let mut sudoku = Sudoku::new();
+        // Only r1c1 and r1c2 can hold 5 within box 1
+        for cell in House::Box(0).cells().into_iter().skip(2) {
+            sudoku.eliminate_candidate(cell.0, cell.1, 5);
+        }
+        let step = pointing(&sudoku).unwrap();
+        assert_eq!(step.technique, Technique::PointingPair);
+        assert_eq!(step.eliminations.len(), 6);
+        assert!(
+            step.eliminations
+                .iter()
+                .all(|&((r, c), n)| r == 0 && c > 2 && n == 5)
+        );
+        assert_eq!(
+            step.explanation,
+            "5 in box 1 can only go in row 1, removing it from the rest of row 1"
+        );
+        assert!(box_line_reduction(&sudoku).is_none());
+    }
+    #[test]
+    fn test_box_line_reduction() {
+        let mut sudoku = Sudoku::new();
+        // Column 9 can only hold 2 inside box 9
+        for row in 0..6 {
+            sudoku.eliminate_candidate(row, 8, 2);
+        }
+        let step = box_line_reduction(&sudoku).unwrap();
+        assert_eq!(step.technique, Technique::BoxLineReduction);
+        assert_eq!(
+            step.detail,
+            Detail::Intersection {
+                digit: 2,
+                intersection: Intersection {
+                    block: House::Box(8),
+                    line: House::Column(8),
+                },
+                cells: vec![(6, 8), (7, 8), (8, 8)],
+            }
+        );
+        assert_eq!(step.eliminations.len(), 6);
+    }
+    #[test]
+    fn test_sue_de_coq() {
+        let mut sudoku = Sudoku::new();
+        keep(&mut sudoku, (0, 0), &[1, 2, 5, 6]);
+        keep(&mut sudoku, (0, 1), &[1, 2, 5, 6]);
+        keep(&mut sudoku, (0, 6), &[1, 5]);
+        keep(&mut sudoku, (1, 2), &[2, 6]);
+        let step = sue_de_coq(&sudoku).unwrap();
+        assert_eq!(
+            step.explanation,
+            "Sue de Coq: r1c1,r1c2 {1,2,5,6} with r1c7 {1,5} in row 1 and r2c3 {2,6} in box 1"
+        );
+        // r1c3 sits in both the line and the box so it loses all four
+        for num in [1, 2, 5, 6] {
+            assert!(step.eliminations.contains(&((0, 2), num)));
+        }
+        assert!(step.eliminations.contains(&((0, 8), 5)));
+        assert!(!step.eliminations.contains(&((0, 8), 2)));
+        assert!(step.eliminations.contains(&((2, 2), 6)));
+        assert!(!step.eliminations.contains(&((2, 2), 1)));
+        assert!(
+            !step
+                .eliminations
+                .iter()
+                .any(|e| e.0 == (0, 6) || e.0 == (1, 2))
+        );
+    }
+}
@@ -0,0 +1,616 @@
+//! Human style solving techniques.
+//!
+//! Every strategy only looks at the board and candidates of a `Sudoku` and reports what it
+//! found as a `Step`, so any frontend can highlight, explain or apply it.
+mod als;
+mod chains;
+mod coloring;
+mod fish;
+mod forcing;
+mod intersections;
+mod pipeline;
+mod single_digit;
+mod singles;
+mod subsets;
+mod uniqueness;
+mod wings;
+
+pub use als::{Als, als_xy_wing, als_xz, death_blossom};
+pub use chains::{MAX_CHAIN_LENGTH, Node, aic, x_cycle, xy_chain};
+pub use coloring::{multi_coloring, simple_coloring};
+pub use fish::{basic_fish, finned_fish};
+pub use forcing::{
+    Branch, MAX_FORCING_DEPTH, cell_forcing_chain, digit_nishio, unit_forcing_chain,
+};
+pub use intersections::{Intersection, box_line_reduction, pointing, sue_de_coq};
+pub use pipeline::Pipeline;
+pub use single_digit::{empty_rectangle, skyscraper, two_string_kite};
+pub use singles::{hidden_singles, naked_singles};
+pub use subsets::{hidden_subset, naked_subset};
+pub use uniqueness::{bug_plus_one, hidden_rectangle, unique_rectangle};
+pub use wings::{w_wing, xy_wing, xyz_wing};
+
+use crate::sudoku::{House, Sudoku, box_of};
+
+/// (row, col) of a cell, zero based
+pub type Cell = (usize, usize);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Technique {
+    NakedSingle,
+    HiddenSingle,
+    NakedPair,
+    NakedTriple,
+    NakedQuad,
+    HiddenPair,
+    HiddenTriple,
+    HiddenQuad,
+    PointingPair,
+    PointingTriple,
+    BoxLineReduction,
+    XWing,
+    Swordfish,
+    Jellyfish,
+    FinnedXWing,
+    FinnedSwordfish,
+    FinnedJellyfish,
+    SashimiXWing,
+    SashimiSwordfish,
+    SashimiJellyfish,
+    XYWing,
+    XYZWing,
+    WWing,
+    Skyscraper,
+    TwoStringKite,
+    EmptyRectangle,
+    SimpleColoring,
+    MultiColoring,
+    XCycle,
+    XYChain,
+    Aic,
+    GroupedAic,
+    UniqueRectangle1,
+    UniqueRectangle2,
+    UniqueRectangle3,
+    UniqueRectangle4,
+    UniqueRectangle5,
+    UniqueRectangle6,
+    HiddenRectangle,
+    BugPlusOne,
+    AlsXz,
+    AlsXyWing,
+    DeathBlossom,
+    SueDeCoq,
+    CellForcingChain,
+    UnitForcingChain,
+    DigitNishio,
+}
+
+impl Technique {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Technique::NakedSingle => "Naked Single",
+            Technique::HiddenSingle => "Hidden Single",
+            Technique::NakedPair => "Naked Pair",
+            Technique::NakedTriple => "Naked Triple",
+            Technique::NakedQuad => "Naked Quad",
+            Technique::HiddenPair => "Hidden Pair",
+            Technique::HiddenTriple => "Hidden Triple",
+            Technique::HiddenQuad => "Hidden Quad",
+            Technique::PointingPair => "Pointing Pair",
+            Technique::PointingTriple => "Pointing Triple",
+            Technique::BoxLineReduction => "Box/Line Reduction",
+            Technique::XWing => "X-Wing",
+            Technique::Swordfish => "Swordfish",
+            Technique::Jellyfish => "Jellyfish",
+            Technique::FinnedXWing => "Finned X-Wing",
+            Technique::FinnedSwordfish => "Finned Swordfish",
+            Technique::FinnedJellyfish => "Finned Jellyfish",
+            Technique::SashimiXWing => "Sashimi X-Wing",
+            Technique::SashimiSwordfish => "Sashimi Swordfish",
+            Technique::SashimiJellyfish => "Sashimi Jellyfish",
+            Technique::XYWing => "XY-Wing",
+            Technique::XYZWing => "XYZ-Wing",
+            Technique::WWing => "W-Wing",
+            Technique::Skyscraper => "Skyscraper",
+            Technique::TwoStringKite => "2-String Kite",
+            Technique::EmptyRectangle => "Empty Rectangle",
+            Technique::SimpleColoring => "Simple Coloring",
+            Technique::MultiColoring => "Multi-Coloring",
+            Technique::XCycle => "X-Cycle",
+            Technique::XYChain => "XY-Chain",
+            Technique::Aic => "Alternating Inference Chain",
+            Technique::GroupedAic => "Grouped AIC",
+            Technique::UniqueRectangle1 => "Unique Rectangle Type 1",
+            Technique::UniqueRectangle2 => "Unique Rectangle Type 2",
+            Technique::UniqueRectangle3 => "Unique Rectangle Type 3",
+            Technique::UniqueRectangle4 => "Unique Rectangle Type 4",
+            Technique::UniqueRectangle5 => "Unique Rectangle Type 5",
+            Technique::UniqueRectangle6 => "Unique Rectangle Type 6",
+            Technique::HiddenRectangle => "Hidden Unique Rectangle",
+            Technique::BugPlusOne => "BUG+1",
+            Technique::AlsXz => "ALS-XZ",
+            Technique::AlsXyWing => "ALS-XY-Wing",
+            Technique::DeathBlossom => "Death Blossom",
+            Technique::SueDeCoq => "Sue de Coq",
+            Technique::CellForcingChain => "Cell Forcing Chain",
+            Technique::UnitForcingChain => "Unit Forcing Chain",
+            Technique::DigitNishio => "Digit Nishio",
+        }
+    }
+}
+
+/// Extra information a strategy attaches to a step so it can be displayed
+#[derive(Debug, Clone, PartialEq)]
+pub enum Detail {
+    None,
+    /// The house that forced a single
+    Single {
+        house: House,
+    },
+    /// Cells of a house that hold the digits of a naked or hidden subset
+    Subset {
+        house: House,
+        cells: Vec<Cell>,
+        digits: u16,
+    },
+    /// A digit of a box and line locked into the cells they share
+    Intersection {
+        digit: usize,
+        intersection: Intersection,
+        cells: Vec<Cell>,
+    },
+    /// Lines whose candidates for a digit are covered by as many crossing lines
+    Fish {
+        digit: usize,
+        base: Vec<House>,
+        cover: Vec<House>,
+        fins: Vec<Cell>,
+    },
+    /// A pivot, or the strong link of a W-Wing, joining pincers that share `digit`
+    Wing {
+        digit: usize,
+        pivot: Option<Cell>,
+        link: Option<(Cell, Cell)>,
+        pincers: Vec<Cell>,
+    },
+    /// Strong links of a single digit that force an elimination
+    Links {
+        digit: usize,
+        strong: Vec<(Cell, Cell)>,
+    },
+    /// Clusters of conjugate pairs split into their two alternating colors
+    Coloring {
+        digit: usize,
+        clusters: Vec<[Vec<Cell>; 2]>,
+    },
+    /// Nodes of an alternating chain, linked strong, weak, strong and so on. A loop also
+    /// links its last node weakly back to the first.
+    Chain {
+        nodes: Vec<Node>,
+        is_loop: bool,
+        eureka: String,
+    },
+    /// Corners of a unique rectangle on two digits
+    Rectangle {
+        digits: u16,
+        cells: Vec<Cell>,
+    },
+    /// Almost locked sets joined by restricted common digits, one per link. Death Blossom
+    /// petals are linked to the stem cell, the other techniques link consecutive sets.
+    Als {
+        sets: Vec<Als>,
+        links: Vec<usize>,
+        stem: Option<Cell>,
+    },
+    /// Intersection cells whose candidates are shared out between cells of the rest of the
+    /// line and cells of the rest of the box
+    SueDeCoq {
+        intersection: Intersection,
+        cells: Vec<Cell>,
+        line_cells: Vec<Cell>,
+        box_cells: Vec<Cell>,
+    },
+    /// Assumptions that were followed, each with the implications leading to its conclusion
+    Forcing {
+        branches: Vec<Branch>,
+    },
+}
+
+/// A single deduction: the digits it places and the candidates it removes
+#[derive(Debug, Clone, PartialEq)]
+pub struct Step {
+    pub technique: Technique,
+    /// Digits that can be placed as (cell, digit)
+    pub placements: Vec<(Cell, usize)>,
+    /// Candidates that can be removed as (cell, digit)
+    pub eliminations: Vec<(Cell, usize)>,
+    /// Candidates that prove the step as (cell, candidate bitmask)
+    pub highlights: Vec<(Cell, u16)>,
+    pub detail: Detail,
+    pub explanation: String,
+}
+
+impl Step {
+    /// Places the digits and removes the candidates of the step. Placements into cells that
+    /// are already filled are skipped, so a batch of steps found on the same board can be
+    /// applied one after another.
+    pub fn apply(&self, sudoku: &mut Sudoku) {
+        for &((row, col), num) in &self.placements {
+            if sudoku.get_board()[row][col] == 0 {
+                sudoku.update_cell(row, col, num);
+            }
+        }
+        for &((row, col), num) in &self.eliminations {
+            sudoku.eliminate_candidate(row, col, num);
+        }
+    }
+    /// Houses a hint can point at without giving the step away: the houses of the pattern,
+    /// or the boxes of its cells when the pattern is not tied to a house
+    pub fn region(&self) -> Vec<House> {
+        let cells: Vec<Cell> = match &self.detail {
+            Detail::Single { house } | Detail::Subset { house, .. } => return vec![*house],
+            Detail::Intersection { intersection, .. } | Detail::SueDeCoq { intersection, .. } => {
+                return vec![intersection.block, intersection.line];
+            }
+            Detail::Fish { base, .. } => return base.clone(),
+            Detail::Wing {
+                pivot,
+                link,
+                pincers,
+                ..
+            } => pivot
+                .iter()
+                .copied()
+                .chain(link.iter().flat_map(|&(a, b)| [a, b]))
+                .chain(pincers.iter().copied())
+                .collect(),
+            Detail::Links { strong, .. } => strong.iter().flat_map(|&(a, b)| [a, b]).collect(),
+            Detail::Coloring { clusters, .. } => {
+                clusters.iter().flatten().flatten().copied().collect()
+            }
+            Detail::Chain { nodes, .. } => nodes
+                .iter()
+                .flat_map(|node| node.cells.iter().copied())
+                .collect(),
+            Detail::Rectangle { cells, .. } => cells.clone(),
+            Detail::Als { sets, stem, .. } => sets
+                .iter()
+                .flat_map(|set| set.cells.iter().copied())
+                .chain(*stem)
+                .collect(),
+            Detail::Forcing { branches } => {
+                branches.iter().map(|branch| branch.assumption.0).collect()
+            }
+            Detail::None => self.highlights.iter().map(|&(cell, _)| cell).collect(),
+        };
+        let mut boxes = vec![];
+        for (row, col) in cells {
+            let house = House::Box(box_of(row, col));
+            if !boxes.contains(&house) {
+                boxes.push(house);
+            }
+        }
+        boxes
+    }
+}
+
+/// Whether two different cells share a row, column or box
+pub fn sees((r1, c1): Cell, (r2, c2): Cell) -> bool {
+    (r1, c1) != (r2, c2) && (r1 == r2 || c1 == c2 || box_of(r1, c1) == box_of(r2, c2))
+}
+
+/// Cells that see every one of the given cells
+pub fn seen_by_all(cells: &[Cell]) -> Vec<Cell> {
+    (0..81)
+        .map(|i| (i / 9, i % 9))
+        .filter(|&cell| cells.iter().all(|&other| sees(cell, other)))
+        .collect()
+}
+
+/// Houses where `num` has exactly two candidates, together with those two cells
+pub fn conjugate_pairs(candidates: &[[u16; 9]; 9], num: usize) -> Vec<(House, Cell, Cell)> {
+    House::all()
+        .filter_map(|house| {
+            let cells: Vec<Cell> = house
+                .cells()
+                .into_iter()
+                .filter(|&(r, c)| candidates[r][c] & crate::sudoku::bit(num) != 0)
+                .collect();
+            match cells[..] {
+                [a, b] => Some((house, a, b)),
+                _ => None,
+            }
+        })
+        .collect()
+}
+
+/// Every way of picking `k` items out of `items`, keeping their order
+pub fn combinations<T: Copy>(items: &[T], k: usize) -> Vec<Vec<T>> {
+    if k == 0 {
+        return vec![vec![]];
+    }
+    let mut result = vec![];
+    for (i, &item) in items.iter().enumerate() {
+        for mut rest in combinations(&items[i + 1..], k - 1) {
+            rest.insert(0, item);
+            result.push(rest);
+        }
+    }
+    result
+}
+
+/// Houses written out as a list, e.g. `row 1, row 5`
+pub fn houses_name(houses: &[House]) -> String {
+    houses
+        .iter()
+        .map(|house| house.to_string())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Digits of a bitmask written out, e.g. `{1,5}` for `0b10001`
+pub fn digits_name(mask: u16) -> String {
+    let list = crate::sudoku::digits(mask)
+        .map(|num| num.to_string())
+        .collect::<Vec<_>>()
+        .join(",");
+    format!("{{{list}}}")
+}
+
+/// Cell in the usual r1c1 notation
+pub fn cell_name((row, col): Cell) -> String {
+    format!("r{}c{}", row + 1, col + 1)
+}
+
+#[cfg(test)]
+pub mod test_util {
+    use super::Step;
+    use crate::sudoku::{House, Sudoku};
+
+    /// Parses an 81 character puzzle where `0` or `.` is an empty cell
+    pub fn sudoku(puzzle: &str) -> Sudoku {
+        let mut board = [[0; 9]; 9];
+        for (i, c) in puzzle.chars().enumerate() {
+            board[i / 9][i % 9] = c.to_digit(10).unwrap_or(0) as usize;
+        }
+        let mut sudoku = Sudoku::new();
+        sudoku.set_board(board);
+        sudoku.set_puzzle(board);
+        sudoku
+    }
+
+    /// Leaves only `nums` as candidates of a cell
+    pub fn keep(sudoku: &mut Sudoku, (row, col): super::Cell, nums: &[usize]) {
+        for num in 1..=9 {
+            if !nums.contains(&num) {
+                sudoku.eliminate_candidate(row, col, num);
+            }
+        }
+    }
+
+    /// Leaves `num` only in the given cells of a house
+    pub fn restrict(sudoku: &mut Sudoku, house: House, num: usize, cells: &[super::Cell]) {
+        for cell in house.cells() {
+            if !cells.contains(&cell) {
+                sudoku.eliminate_candidate(cell.0, cell.1, num);
+            }
+        }
+    }
+
+    /// Solution from the fast solver, only used to check that steps agree with the real
+    /// solution
+    pub fn solution(sudoku: &Sudoku) -> [[usize; 9]; 9] {
+        crate::solver::solve(sudoku.get_board()).expect("puzzle has no solution")
+    }
+
+    /// A step is sound when it never places a wrong digit or removes the correct one
+    pub fn assert_sound(step: &Step, solution: &[[usize; 9]; 9]) {
+        for &((row, col), num) in &step.placements {
+            assert_eq!(solution[row][col], num, "wrong placement in {step:?}");
+        }
+        for &((row, col), num) in &step.eliminations {
+            assert_ne!(
+                solution[row][col], num,
+                "removed solution digit in {step:?}"
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::test_util::{assert_sound, solution, sudoku};
+    use super::*;
+
+    /// Puzzles that between them need every technique in the pipeline
+    const SOUNDNESS_PUZZLES: [&str; 20] = [
+        "470000000020301005010540006750009000040823000000010002900008003000000700000000020",
+        "000500009095000086107040300000023000650008000009007000000000800201090007030000010",
+        "000000005030070800517000900906100008004007300000900060000060057405090000002508000",
+        "000000500807500010000000090000007060500092004008100007000000076030004000702035000",
+        "000010008500600019007000400071300805002070600003008000060500000000000900030107050",
+        "002000000705203004000710003340000700008000000057000398090000000804000005003800027",
+        "030005060007060200010000000000900000960080003000530072400170500000009400500003600",
+        "000000300400000071000009060600340800008000000203001004806200000000970005020080007",
+        "000409000040006100800010020600000400000090070010002085009000060083040000000020007",
+        "500840001090030400007000000008300900000096304960000000000100006000063700070005008",
+        "300002000090840000001300800540000600000090005008000040010056007000000201000020003",
+        "800006004004700000090003800630900700000000920009010005000009030000000248000401006",
+        "010090004867400900000065000200000400006030010070001290000009180002100000000040602",
+        "009001000000070050000060004100009002806307400000400000080003020904000001015000703",
+        "900300210000090060170200000000010000000800600020400008000003805802105300504007006",
+        "403000601000902040000000700030020080050601400070000000800000010006310098000009000",
+        "501000003060010000000400009005000070600000001007009200004000800020030004070906000",
+        "800010900000080205040000000050140060002009040000760000000006370000000002309200001",
+        "004070001100500908030400000000030200300100090000080004000367800800000009050000720",
+        "540090000200000014300000900004009350000008700000100400008205006070030005000900070",
+    ];
+
+    /// Strategies every harder one is tried on top of
+    const BASICS: [&str; 10] = [
+        "Naked Single",
+        "Hidden Single",
+        "Pointing",
+        "Box/Line Reduction",
+        "Naked Pair",
+        "Hidden Pair",
+        "Naked Triple",
+        "Hidden Triple",
+        "Naked Quad",
+        "Hidden Quad",
+    ];
+
+    #[test]
+    fn test_strategies_are_sound() {
+        let mut used = vec![];
+        let basics = Pipeline::default().filter(|s| BASICS.contains(&s.name()));
+        // Boards where the basics get stuck, which is where the harder strategies come in
+        let mut stuck = vec![];
+        for puzzle in SOUNDNESS_PUZZLES {
+            let mut sudoku = sudoku(puzzle);
+            sudoku.set_unique_solution(true);
+            let solution = solution(&sudoku);
+            for step in basics.solve(&mut sudoku) {
+                assert_sound(&step, &solution);
+                used.push(step.technique);
+            }
+            if sudoku.get_board() != solution {
+                stuck.push((sudoku, solution));
+            }
+        }
+        for strategy in Pipeline::default().strategies() {
+            if BASICS.contains(&strategy.name()) {
+                continue;
+            }
+            // Each strategy gets the stuck boards to itself, so a cheaper one cannot hide it
+            let pipeline = Pipeline::default()
+                .filter(|s| BASICS.contains(&s.name()) || s.name() == strategy.name());
+            for (sudoku, solution) in &stuck {
+                let mut sudoku = sudoku.clone();
+                for step in pipeline.solve(&mut sudoku) {
+                    assert_sound(&step, solution);
+                    used.push(step.technique);
+                }
+            }
+        }
+        // The basics never strip the extra digit from a Type 5 floor alone, since the floor
+        // shares a house with the roof, so that one needs the whole pipeline to clear the way
+        for (sudoku, solution) in &stuck {
+            let mut sudoku = sudoku.clone();
+            for step in Pipeline::default().solve(&mut sudoku) {
+                assert_sound(&step, solution);
+                used.push(step.technique);
+            }
+        }
+        for technique in [
+            Technique::PointingPair,
+            Technique::BoxLineReduction,
+            Technique::NakedPair,
+            Technique::HiddenPair,
+            Technique::NakedTriple,
+            Technique::XWing,
+            Technique::Swordfish,
+            Technique::FinnedXWing,
+            Technique::SashimiXWing,
+            Technique::SashimiSwordfish,
+            Technique::XYWing,
+            Technique::XYZWing,
+            Technique::WWing,
+            Technique::Skyscraper,
+            Technique::TwoStringKite,
+            Technique::EmptyRectangle,
+            Technique::SimpleColoring,
+            Technique::MultiColoring,
+            Technique::XCycle,
+            Technique::XYChain,
+            Technique::Aic,
+            Technique::GroupedAic,
+            Technique::UniqueRectangle1,
+            Technique::UniqueRectangle2,
+            Technique::UniqueRectangle3,
+            Technique::UniqueRectangle4,
+            Technique::UniqueRectangle5,
+            Technique::UniqueRectangle6,
+            Technique::HiddenRectangle,
+            Technique::BugPlusOne,
+            Technique::AlsXz,
+            Technique::AlsXyWing,
+            Technique::DeathBlossom,
+            Technique::SueDeCoq,
+            Technique::DigitNishio,
+            Technique::CellForcingChain,
+            Technique::UnitForcingChain,
+        ] {
+            assert!(used.contains(&technique), "{technique:?} never used");
+        }
+    }
+
+    #[test]
+    fn test_cell_name() {
+        assert_eq!(cell_name((2, 4)), "r3c5");
+    }
+    #[test]
+    fn test_seen_by_all() {
+        assert!(sees((0, 0), (2, 2)));
+        assert!(!sees((0, 0), (0, 0)));
+        assert!(!sees((0, 0), (3, 3)));
+        assert_eq!(seen_by_all(&[(0, 0), (3, 3)]), vec![(0, 3), (3, 0)]);
+    }
+    #[test]
+    fn test_combinations() {
+        assert_eq!(
+            combinations(&[1, 2, 3], 2),
+            vec![vec![1, 2], vec![1, 3], vec![2, 3]]
+        );
+        assert_eq!(combinations(&[1, 2], 3), Vec::<Vec<i32>>::new());
+        assert_eq!(digits_name(0b10001), "{1,5}");
+    }
+    #[test]
+    fn test_step_apply() {
+        let mut sudoku = Sudoku::new();
+        let step = Step {
+            technique: Technique::NakedSingle,
+            placements: vec![((0, 0), 5)],
+            eliminations: vec![((4, 4), 3)],
+            highlights: vec![],
+            detail: Detail::None,
+            explanation: String::new(),
+        };
+        step.apply(&mut sudoku);
+        assert_eq!(sudoku.get_board()[0][0], 5);
+        assert!(!sudoku.has_candidate(0, 8, 5));
+        assert!(!sudoku.has_candidate(4, 4, 3));
+    }
+    #[test]
+    fn test_step_region() {
+        let mut sudoku = sudoku(
+            "003020600900305001001806400008102900700000008006708200002609500800203009005010300",
+        );
+        let naked = naked_singles(&sudoku).remove(0);
+        let (row, col) = naked.placements[0].0;
+        assert_eq!(naked.region(), vec![House::Box(box_of(row, col))]);
+        sudoku.update_cell(row, col, naked.placements[0].1);
+        let hidden = hidden_singles(&sudoku).remove(0);
+        let Detail::Single { house } = hidden.detail else {
+            panic!("hidden single without a house");
+        };
+        assert_eq!(hidden.region(), vec![house]);
+    }
+    #[test]
+    fn test_apply_skips_filled_cells() {
+        let mut sudoku = sudoku(
+            "003020600900305001001806400008102900700000008006708200002609500800203009005010300",
+        );
+        let steps = naked_singles(&sudoku);
+        let mut duplicate = steps.clone();
+        duplicate.extend(steps.iter().cloned());
+        for step in &duplicate {
+            step.apply(&mut sudoku);
+        }
+        for step in &steps {
+            let ((row, col), num) = step.placements[0];
+            assert_eq!(sudoku.get_board()[row][col], num);
+        }
+    }
+}
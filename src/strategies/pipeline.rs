@@ -0,0 +1,361 @@
+use super::*;
+
+/// A solving technique the pipeline can try
+pub trait Strategy {
+    fn name(&self) -> &str;
+    /// Technique of the easiest step the strategy takes
+    fn technique(&self) -> Technique;
+    /// Cost of using the technique, cheaper strategies are tried first
+    fn difficulty(&self) -> u32;
+    fn find_step(&self, sudoku: &Sudoku) -> Option<Step>;
+}
+
+type Find = Box<dyn Fn(&Sudoku) -> Option<Step>>;
+
+/// A strategy backed by a function, which may capture settings such as a chain length
+pub struct StrategyFn {
+    name: String,
+    technique: Technique,
+    difficulty: u32,
+    find: Find,
+}
+
+impl StrategyFn {
+    pub fn new(
+        name: &str,
+        technique: Technique,
+        difficulty: u32,
+        find: impl Fn(&Sudoku) -> Option<Step> + 'static,
+    ) -> Self {
+        Self {
+            name: name.to_string(),
+            technique,
+            difficulty,
+            find: Box::new(find),
+        }
+    }
+}
+
+impl Strategy for StrategyFn {
+    fn name(&self) -> &str {
+        &self.name
+    }
+    fn technique(&self) -> Technique {
+        self.technique
+    }
+    fn difficulty(&self) -> u32 {
+        self.difficulty
+    }
+    fn find_step(&self, sudoku: &Sudoku) -> Option<Step> {
+        (self.find)(sudoku)
+    }
+}
+
+/// Strategies ordered from cheapest to most expensive
+pub struct Pipeline {
+    strategies: Vec<Box<dyn Strategy>>,
+}
+
+impl Default for Pipeline {
+    /// Every implemented strategy, weighted roughly like HoDoKu scores its steps
+    fn default() -> Self {
+        Self::new(vec![
+            strategy("Naked Single", Technique::NakedSingle, 4, |s| {
+                naked_singles(s).into_iter().next()
+            }),
+            strategy("Hidden Single", Technique::HiddenSingle, 14, |s| {
+                hidden_singles(s).into_iter().next()
+            }),
+            strategy("Pointing", Technique::PointingPair, 50, pointing),
+            strategy(
+                "Box/Line Reduction",
+                Technique::BoxLineReduction,
+                50,
+                box_line_reduction,
+            ),
+            strategy("Naked Pair", Technique::NakedPair, 60, |s| {
+                naked_subset(s, 2)
+            }),
+            strategy("Hidden Pair", Technique::HiddenPair, 70, |s| {
+                hidden_subset(s, 2)
+            }),
+            strategy("Naked Triple", Technique::NakedTriple, 80, |s| {
+                naked_subset(s, 3)
+            }),
+            strategy("Hidden Triple", Technique::HiddenTriple, 100, |s| {
+                hidden_subset(s, 3)
+            }),
+            strategy("Naked Quad", Technique::NakedQuad, 120, |s| {
+                naked_subset(s, 4)
+            }),
+            strategy("Hidden Quad", Technique::HiddenQuad, 150, |s| {
+                hidden_subset(s, 4)
+            }),
+            strategy("X-Wing", Technique::XWing, 140, |s| basic_fish(s, 2)),
+            strategy("Swordfish", Technique::Swordfish, 150, |s| basic_fish(s, 3)),
+            strategy("Jellyfish", Technique::Jellyfish, 160, |s| basic_fish(s, 4)),
+            strategy("Finned X-Wing", Technique::FinnedXWing, 150, |s| {
+                finned_fish(s, 2)
+            }),
+            strategy("Finned Swordfish", Technique::FinnedSwordfish, 200, |s| {
+                finned_fish(s, 3)
+            }),
+            strategy("Finned Jellyfish", Technique::FinnedJellyfish, 250, |s| {
+                finned_fish(s, 4)
+            }),
+            strategy(
+                "Empty Rectangle",
+                Technique::EmptyRectangle,
+                120,
+                empty_rectangle,
+            ),
+            strategy("Skyscraper", Technique::Skyscraper, 130, skyscraper),
+            strategy(
+                "2-String Kite",
+                Technique::TwoStringKite,
+                150,
+                two_string_kite,
+            ),
+            strategy("W-Wing", Technique::WWing, 150, w_wing),
+            strategy("XY-Wing", Technique::XYWing, 160, xy_wing),
+            strategy("XYZ-Wing", Technique::XYZWing, 180, xyz_wing),
+            strategy(
+                "Simple Coloring",
+                Technique::SimpleColoring,
+                150,
+                simple_coloring,
+            ),
+            strategy(
+                "Multi-Coloring",
+                Technique::MultiColoring,
+                200,
+                multi_coloring,
+            ),
+            strategy(
+                "Unique Rectangle Type 1",
+                Technique::UniqueRectangle1,
+                100,
+                |s| unique_rectangle(s, 1),
+            ),
+            strategy(
+                "Unique Rectangle Type 2",
+                Technique::UniqueRectangle2,
+                100,
+                |s| unique_rectangle(s, 2),
+            ),
+            strategy(
+                "Unique Rectangle Type 3",
+                Technique::UniqueRectangle3,
+                100,
+                |s| unique_rectangle(s, 3),
+            ),
+            strategy(
+                "Unique Rectangle Type 4",
+                Technique::UniqueRectangle4,
+                100,
+                |s| unique_rectangle(s, 4),
+            ),
+            strategy(
+                "Unique Rectangle Type 5",
+                Technique::UniqueRectangle5,
+                100,
+                |s| unique_rectangle(s, 5),
+            ),
+            strategy(
+                "Unique Rectangle Type 6",
+                Technique::UniqueRectangle6,
+                100,
+                |s| unique_rectangle(s, 6),
+            ),
+            strategy(
+                "Hidden Unique Rectangle",
+                Technique::HiddenRectangle,
+                100,
+                hidden_rectangle,
+            ),
+            strategy("BUG+1", Technique::BugPlusOne, 100, bug_plus_one),
+            strategy("Sue de Coq", Technique::SueDeCoq, 250, sue_de_coq),
+            strategy("X-Cycle", Technique::XCycle, 260, |s| {
+                x_cycle(s, MAX_CHAIN_LENGTH)
+            }),
+            strategy("XY-Chain", Technique::XYChain, 260, |s| {
+                xy_chain(s, MAX_CHAIN_LENGTH)
+            }),
+            strategy("Alternating Inference Chain", Technique::Aic, 280, |s| {
+                aic(s, MAX_CHAIN_LENGTH)
+            }),
+            strategy("ALS-XZ", Technique::AlsXz, 300, als_xz),
+            strategy("ALS-XY-Wing", Technique::AlsXyWing, 320, als_xy_wing),
+            strategy("Death Blossom", Technique::DeathBlossom, 360, death_blossom),
+            strategy("Digit Nishio", Technique::DigitNishio, 450, |s| {
+                digit_nishio(s, MAX_FORCING_DEPTH)
+            }),
+            strategy(
+                "Cell Forcing Chain",
+                Technique::CellForcingChain,
+                500,
+                |s| cell_forcing_chain(s, MAX_FORCING_DEPTH),
+            ),
+            strategy(
+                "Unit Forcing Chain",
+                Technique::UnitForcingChain,
+                500,
+                |s| unit_forcing_chain(s, MAX_FORCING_DEPTH),
+            ),
+        ])
+    }
+}
+
+fn strategy(
+    name: &str,
+    technique: Technique,
+    difficulty: u32,
+    find: impl Fn(&Sudoku) -> Option<Step> + 'static,
+) -> Box<dyn Strategy> {
+    Box::new(StrategyFn::new(name, technique, difficulty, find))
+}
+
+impl Pipeline {
+    pub fn new(mut strategies: Vec<Box<dyn Strategy>>) -> Self {
+        strategies.sort_by_key(|strategy| strategy.difficulty());
+        Self { strategies }
+    }
+    pub fn strategies(&self) -> &[Box<dyn Strategy>] {
+        &self.strategies
+    }
+    /// Keeps only the strategies `keep` returns true for
+    pub fn filter(mut self, keep: impl Fn(&dyn Strategy) -> bool) -> Self {
+        self.strategies.retain(|strategy| keep(strategy.as_ref()));
+        self
+    }
+    /// Reorders the strategies by `key`, lowest first. Their difficulty stays the same, so
+    /// weighted steps keep their usual score.
+    pub fn sorted_by(mut self, key: impl Fn(&dyn Strategy) -> f32) -> Self {
+        self.strategies
+            .sort_by(|a, b| key(a.as_ref()).total_cmp(&key(b.as_ref())));
+        self
+    }
+    /// Step of the cheapest strategy that makes progress
+    pub fn next_step(&self, sudoku: &Sudoku) -> Option<Step> {
+        self.next_weighted_step(sudoku).map(|(_, step)| step)
+    }
+    /// Like `next_step`, together with the difficulty of the strategy that found it
+    pub fn next_weighted_step(&self, sudoku: &Sudoku) -> Option<(u32, Step)> {
+        self.strategies.iter().find_map(|strategy| {
+            strategy
+                .find_step(sudoku)
+                .map(|step| (strategy.difficulty(), step))
+        })
+    }
+    /// Applies steps until the board is full or no strategy makes progress, returning the
+    /// steps in the order they were applied
+    pub fn solve(&self, sudoku: &mut Sudoku) -> Vec<Step> {
+        self.solve_weighted(sudoku)
+            .into_iter()
+            .map(|(_, step)| step)
+            .collect()
+    }
+    /// Like `solve`, keeping the difficulty of the strategy behind every step
+    pub fn solve_weighted(&self, sudoku: &mut Sudoku) -> Vec<(u32, Step)> {
+        let mut steps = vec![];
+        while sudoku.get_board().iter().flatten().any(|&num| num == 0) {
+            let Some((weight, step)) = self.next_weighted_step(sudoku) else {
+                break;
+            };
+            let before = (sudoku.get_board(), sudoku.get_candidates());
+            step.apply(sudoku);
+            if (sudoku.get_board(), sudoku.get_candidates()) == before {
+                break;
+            }
+            steps.push((weight, step));
+        }
+        steps
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::strategies::test_util::{assert_sound, solution, sudoku};
+
+    #[test]
+    fn test_default_is_ordered() {
+        let pipeline = Pipeline::default();
+        let weights: Vec<u32> = pipeline
+            .strategies()
+            .iter()
+            .map(|s| s.difficulty())
+            .collect();
+        assert!(weights.windows(2).all(|pair| pair[0] <= pair[1]));
+        assert_eq!(pipeline.strategies()[0].name(), "Naked Single");
+    }
+    #[test]
+    fn test_cheapest_strategy_first() {
+        let pipeline = Pipeline::new(vec![
+            Box::new(StrategyFn::new("Expensive", Technique::XWing, 10, |_| {
+                Some(Step {
+                    technique: Technique::XWing,
+                    placements: vec![],
+                    eliminations: vec![],
+                    highlights: vec![],
+                    detail: Detail::None,
+                    explanation: String::new(),
+                })
+            })),
+            Box::new(StrategyFn::new(
+                "Naked Single",
+                Technique::NakedSingle,
+                1,
+                |s| naked_singles(s).into_iter().next(),
+            )),
+        ]);
+        let mut sudoku = Sudoku::new();
+        for num in 1..=8 {
+            sudoku.update_cell(0, num - 1, num);
+        }
+        let step = pipeline.next_step(&sudoku).unwrap();
+        assert_eq!(step.technique, Technique::NakedSingle);
+        let pipeline = pipeline.filter(|s| s.name() != "Naked Single");
+        assert_eq!(
+            pipeline.next_step(&sudoku).unwrap().technique,
+            Technique::XWing
+        );
+    }
+    #[test]
+    fn test_sorted_by_keeps_difficulty() {
+        let pipeline = Pipeline::default().sorted_by(|s| -(s.difficulty() as f32));
+        assert_eq!(pipeline.strategies()[0].difficulty(), 500);
+        let last = pipeline.strategies().last().unwrap();
+        assert_eq!((last.name(), last.difficulty()), ("Naked Single", 4));
+    }
+    #[test]
+    fn test_solve_returns_ordered_steps() {
+        let puzzle =
+            "800006004004700000090003800630900700000000920009010005000009030000000248000401006";
+        let mut hard = sudoku(puzzle);
+        hard.set_unique_solution(true);
+        let solved = solution(&hard);
+        let steps = Pipeline::default().solve(&mut hard);
+        assert_eq!(hard.get_board(), solved);
+        assert_eq!(steps[0].technique, Technique::NakedSingle);
+        assert!(
+            steps
+                .iter()
+                .any(|s| s.technique.name().contains("Rectangle"))
+        );
+        for step in &steps {
+            assert_sound(step, &solved);
+        }
+    }
+    #[test]
+    fn test_solve_stops_when_stuck() {
+        let puzzle =
+            "800006004004700000090003800630900700000000920009010005000009030000000248000401006";
+        let mut stuck = sudoku(puzzle);
+        let pipeline = Pipeline::default().filter(|s| s.difficulty() < 20);
+        let steps = pipeline.solve(&mut stuck);
+        assert!(!steps.is_empty());
+        assert!(stuck.get_board().iter().flatten().any(|&num| num == 0));
+        assert!(pipeline.next_step(&stuck).is_none());
+    }
+}
@@ -0,0 +1,261 @@
+use super::{Cell, Detail, Step, Technique, cell_name, conjugate_pairs, seen_by_all, sees};
+use crate::sudoku::{House, Sudoku, bit, box_of};
+
+/// Two parallel strong links whose bases share a line. One of the two tops must hold the
+/// digit, so it goes from every cell that sees both tops.
+pub fn skyscraper(sudoku: &Sudoku) -> Option<Step> {
+    let candidates = sudoku.get_candidates();
+    for num in 1..=9 {
+        let pairs = conjugate_pairs(&candidates, num);
+        for (i, &(h1, a1, b1)) in pairs.iter().enumerate() {
+            for &(h2, a2, b2) in &pairs[i + 1..] {
+                let parallel = matches!(
+                    (h1, h2),
+                    (House::Row(_), House::Row(_)) | (House::Column(_), House::Column(_))
+                );
+                if !parallel {
+                    continue;
+                }
+                let same_line = |x: Cell, y: Cell| match h1 {
+                    House::Row(_) => x.1 == y.1,
+                    _ => x.0 == y.0,
+                };
+                for (base1, top1) in [(a1, b1), (b1, a1)] {
+                    for (base2, top2) in [(a2, b2), (b2, a2)] {
+                        if !same_line(base1, base2) || same_line(top1, top2) {
+                            continue;
+                        }
+                        let step = turbot_step(
+                            Technique::Skyscraper,
+                            &candidates,
+                            num,
+                            [(base1, top1), (base2, top2)],
+                        );
+                        if step.is_some() {
+                            return step;
+                        }
+                    }
+                }
+            }
+        }
+    }
+    None
+}
+
+/// A strong link in a row and one in a column with an end of each in the same box. One of
+/// the two far ends must hold the digit, so it goes from every cell that sees both of them.
+pub fn two_string_kite(sudoku: &Sudoku) -> Option<Step> {
+    let candidates = sudoku.get_candidates();
+    for num in 1..=9 {
+        let pairs = conjugate_pairs(&candidates, num);
+        let rows = pairs.iter().filter(|p| matches!(p.0, House::Row(_)));
+        for &(_, a1, b1) in rows {
+            let cols = pairs.iter().filter(|p| matches!(p.0, House::Column(_)));
+            for &(_, a2, b2) in cols {
+                for (near1, far1) in [(a1, b1), (b1, a1)] {
+                    for (near2, far2) in [(a2, b2), (b2, a2)] {
+                        let cells = [near1, far1, near2, far2];
+                        let distinct = (0..4).all(|i| (i + 1..4).all(|j| cells[i] != cells[j]));
+                        if !distinct || box_of(near1.0, near1.1) != box_of(near2.0, near2.1) {
+                            continue;
+                        }
+                        let step = turbot_step(
+                            Technique::TwoStringKite,
+                            &candidates,
+                            num,
+                            [(near1, far1), (near2, far2)],
+                        );
+                        if step.is_some() {
+                            return step;
+                        }
+                    }
+                }
+            }
+        }
+    }
+    None
+}
+
+/// Two strong links (base = end) whose bases see each other
+fn turbot_step(
+    technique: Technique,
+    candidates: &[[u16; 9]; 9],
+    num: usize,
+    links: [(Cell, Cell); 2],
+) -> Option<Step> {
+    let ends = [links[0].1, links[1].1];
+    if sees(ends[0], ends[1]) {
+        return None;
+    }
+    let eliminations: Vec<(Cell, usize)> = seen_by_all(&ends)
+        .into_iter()
+        .filter(|&(r, c)| candidates[r][c] & bit(num) != 0)
+        .map(|cell| (cell, num))
+        .collect();
+    if eliminations.is_empty() {
+        return None;
+    }
+    let strong = links.to_vec();
+    Some(links_step(technique, num, strong, eliminations))
+}
+
+/// A box whose candidates for a digit all sit on one row and one column of it, plus a
+/// strong link outside the box with an end on that row (or column). If the far end of the
+/// link is false the box is forced onto its column (or row), so the cell where the far
+/// end's line crosses that column (or row) can never hold the digit.
+pub fn empty_rectangle(sudoku: &Sudoku) -> Option<Step> {
+    let candidates = sudoku.get_candidates();
+    let has = |(r, c): Cell, num: usize| candidates[r][c] & bit(num) != 0;
+    for num in 1..=9 {
+        let pairs = conjugate_pairs(&candidates, num);
+        for b in 0..9 {
+            let cells: Vec<Cell> = House::Box(b)
+                .cells()
+                .into_iter()
+                .filter(|&cell| has(cell, num))
+                .collect();
+            let single_line =
+                cells.iter().all(|c| c.0 == cells[0].0) || cells.iter().all(|c| c.1 == cells[0].1);
+            if cells.len() < 2 || single_line {
+                continue;
+            }
+            for (er_row, er_col) in House::Box(b).cells() {
+                if !cells.iter().all(|&(r, c)| r == er_row || c == er_col) {
+                    continue;
+                }
+                for &(house, p, q) in &pairs {
+                    for (near, far) in [(p, q), (q, p)] {
+                        if box_of(near.0, near.1) == b || box_of(far.0, far.1) == b {
+                            continue;
+                        }
+                        // A link along a column touches the box row, one along a row
+                        // touches the box column
+                        let target = match house {
+                            House::Column(_) if near.0 == er_row => (far.0, er_col),
+                            House::Row(_) if near.1 == er_col => (er_row, far.1),
+                            _ => continue,
+                        };
+                        if box_of(target.0, target.1) == b || !has(target, num) {
+                            continue;
+                        }
+                        let mut step = links_step(
+                            Technique::EmptyRectangle,
+                            num,
+                            vec![(near, far)],
+                            vec![(target, num)],
+                        );
+                        step.highlights
+                            .extend(cells.iter().map(|&cell| (cell, bit(num))));
+                        step.explanation = format!(
+                            "{} in {} only sits on row {} and column {}, with {}",
+                            num,
+                            House::Box(b),
+                            er_row + 1,
+                            er_col + 1,
+                            step.explanation
+                        );
+                        return Some(step);
+                    }
+                }
+            }
+        }
+    }
+    None
+}
+
+fn links_step(
+    technique: Technique,
+    num: usize,
+    strong: Vec<(Cell, Cell)>,
+    eliminations: Vec<(Cell, usize)>,
+) -> Step {
+    let names = strong
+        .iter()
+        .map(|&(a, b)| format!("{}={}", cell_name(a), cell_name(b)))
+        .collect::<Vec<_>>()
+        .join(", ");
+    Step {
+        technique,
+        placements: vec![],
+        highlights: strong
+            .iter()
+            .flat_map(|&(a, b)| [(a, bit(num)), (b, bit(num))])
+            .collect(),
+        explanation: format!(
+            "{}: strong links {} on {} remove it from {}",
+            technique.name(),
+            names,
+            num,
+            eliminations
+                .iter()
+                .map(|&(cell, _)| cell_name(cell))
+                .collect::<Vec<_>>()
+                .join(",")
+        ),
+        eliminations,
+        detail: Detail::Links { digit: num, strong },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::strategies::test_util::restrict;
+
+    #[test]
+    fn test_skyscraper() {
+        let mut sudoku = Sudoku::new();
+        restrict(&mut sudoku, House::Row(0), 1, &[(0, 0), (0, 4)]);
+        restrict(&mut sudoku, House::Row(6), 1, &[(6, 0), (6, 5)]);
+        let step = skyscraper(&sudoku).unwrap();
+        assert_eq!(
+            step.detail,
+            Detail::Links {
+                digit: 1,
+                strong: vec![((0, 0), (0, 4)), ((6, 0), (6, 5))],
+            }
+        );
+        // Each top sees the other's box through its own column
+        assert_eq!(
+            step.eliminations,
+            vec![((1, 5), 1), ((2, 5), 1), ((7, 4), 1), ((8, 4), 1)]
+        );
+    }
+    #[test]
+    fn test_two_string_kite() {
+        let mut sudoku = Sudoku::new();
+        restrict(&mut sudoku, House::Row(1), 6, &[(1, 2), (1, 7)]);
+        restrict(&mut sudoku, House::Column(0), 6, &[(0, 0), (5, 0)]);
+        let step = two_string_kite(&sudoku).unwrap();
+        assert_eq!(step.technique, Technique::TwoStringKite);
+        assert_eq!(
+            step.detail,
+            Detail::Links {
+                digit: 6,
+                strong: vec![((1, 2), (1, 7)), ((0, 0), (5, 0))],
+            }
+        );
+        assert_eq!(step.eliminations, vec![((5, 7), 6)]);
+    }
+    #[test]
+    fn test_empty_rectangle() {
+        let mut sudoku = Sudoku::new();
+        // Box 5 only holds 3 on row 5 and column 5
+        restrict(
+            &mut sudoku,
+            House::Box(4),
+            3,
+            &[(4, 3), (4, 5), (3, 4), (5, 4)],
+        );
+        restrict(&mut sudoku, House::Column(7), 3, &[(4, 7), (8, 7)]);
+        let step = empty_rectangle(&sudoku).unwrap();
+        assert_eq!(
+            step.detail,
+            Detail::Links {
+                digit: 3,
+                strong: vec![((4, 7), (8, 7))],
+            }
+        );
+        assert_eq!(step.eliminations, vec![((8, 4), 3)]);
+    }
+}
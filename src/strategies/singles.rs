@@ -0,0 +1,169 @@
+use super::{Detail, Step, Technique, cell_name};
+use crate::sudoku::{House, Sudoku, bit, digits};
+
+/// Empty cells that are left with exactly one candidate.
+/// Every single is returned as its own step so they can be shown one at a time.
+pub fn naked_singles(sudoku: &Sudoku) -> Vec<Step> {
+    let board = sudoku.get_board();
+    let candidates = sudoku.get_candidates();
+    let mut steps = vec![];
+    for row in 0..9 {
+        for col in 0..9 {
+            let mask = candidates[row][col];
+            if board[row][col] != 0 || mask.count_ones() != 1 {
+                continue;
+            }
+            let num = digits(mask).next().unwrap();
+            steps.push(Step {
+                technique: Technique::NakedSingle,
+                placements: vec![((row, col), num)],
+                eliminations: vec![],
+                highlights: vec![((row, col), mask)],
+                detail: Detail::None,
+                explanation: format!("{} can only be {}", cell_name((row, col)), num),
+            });
+        }
+    }
+    steps
+}
+
+/// Digits that fit in only one cell of a row, column or box.
+/// A cell found through several houses is only reported for the first one.
+pub fn hidden_singles(sudoku: &Sudoku) -> Vec<Step> {
+    let board = sudoku.get_board();
+    let candidates = sudoku.get_candidates();
+    let mut steps: Vec<Step> = vec![];
+    for house in House::all() {
+        for num in 1..=9 {
+            let mut spots = house
+                .cells()
+                .into_iter()
+                .filter(|&(r, c)| board[r][c] == 0 && candidates[r][c] & bit(num) != 0);
+            let (Some(cell), None) = (spots.next(), spots.next()) else {
+                continue;
+            };
+            if steps.iter().any(|s| s.placements[0] == (cell, num)) {
+                continue;
+            }
+            steps.push(Step {
+                technique: Technique::HiddenSingle,
+                placements: vec![(cell, num)],
+                eliminations: vec![],
+                highlights: vec![(cell, bit(num))],
+                detail: Detail::Single { house },
+                explanation: format!(
+                    "{} can only go in {} within {}",
+                    num,
+                    cell_name(cell),
+                    house
+                ),
+            });
+        }
+    }
+    steps
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::strategies::test_util::{assert_sound, solution, sudoku};
+
+    const EASY: &str =
+        "003020600900305001001806400008102900700000008006708200002609500800203009005010300";
+
+    #[test]
+    fn test_naked_single() {
+        let mut sudoku = Sudoku::new();
+        for (col, num) in (1..9).zip(1..9) {
+            sudoku.update_cell(0, col, num);
+        }
+        let steps = naked_singles(&sudoku);
+        assert_eq!(steps.len(), 1);
+        assert_eq!(steps[0].placements, vec![((0, 0), 9)]);
+        assert_eq!(steps[0].explanation, "r1c1 can only be 9");
+    }
+    #[test]
+    fn test_no_naked_single_on_empty_board() {
+        assert!(naked_singles(&Sudoku::new()).is_empty());
+    }
+    #[test]
+    fn test_hidden_single() {
+        let mut sudoku = Sudoku::new();
+        // Row 1 holds a 7 and the rest of box 2 is filled, leaving r3c5 for the 7
+        sudoku.update_cell(0, 0, 7);
+        for (cell, num) in [
+            ((1, 3), 1),
+            ((1, 4), 2),
+            ((1, 5), 3),
+            ((2, 3), 4),
+            ((2, 5), 5),
+        ] {
+            sudoku.update_cell(cell.0, cell.1, num);
+        }
+        let step = hidden_singles(&sudoku)
+            .into_iter()
+            .find(|s| {
+                s.detail
+                    == Detail::Single {
+                        house: House::Box(1),
+                    }
+            })
+            .unwrap();
+        assert_eq!(step.placements, vec![((2, 4), 7)]);
+        assert_eq!(step.explanation, "7 can only go in r3c5 within box 2");
+    }
+    #[test]
+    fn test_hidden_singles_reported_once() {
+        let mut sudoku = Sudoku::new();
+        for (col, num) in (1..9).zip(1..9) {
+            sudoku.update_cell(0, col, num);
+        }
+        // r1c1 is the only 9 in row 1, column 1 and box 1 at once
+        for row in 1..9 {
+            sudoku.eliminate_candidate(row, 0, 9);
+        }
+        let steps = hidden_singles(&sudoku);
+        let count = steps
+            .iter()
+            .filter(|s| s.placements == vec![((0, 0), 9)])
+            .count();
+        assert_eq!(count, 1);
+        assert_eq!(
+            steps[0].detail,
+            Detail::Single {
+                house: House::Row(0)
+            }
+        );
+    }
+    #[test]
+    fn test_naked_singles_solve_easy() {
+        let mut sudoku = sudoku(EASY);
+        let solution = solution(&sudoku);
+        loop {
+            let steps = naked_singles(&sudoku);
+            if steps.is_empty() {
+                break;
+            }
+            steps.iter().for_each(|step| assert_sound(step, &solution));
+            steps.iter().for_each(|step| step.apply(&mut sudoku));
+        }
+        assert_eq!(sudoku.get_board(), solution);
+    }
+    #[test]
+    fn test_singles_solve_medium() {
+        let mut sudoku = sudoku(
+            "000260701680070090190004500820100040004602900050003028009300074040050036703018000",
+        );
+        let solution = solution(&sudoku);
+        loop {
+            let mut steps = hidden_singles(&sudoku);
+            steps.extend(naked_singles(&sudoku));
+            if steps.is_empty() {
+                break;
+            }
+            steps.iter().for_each(|step| assert_sound(step, &solution));
+            steps.iter().for_each(|step| step.apply(&mut sudoku));
+        }
+        assert_eq!(sudoku.get_board(), solution);
+    }
+}
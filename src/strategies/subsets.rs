@@ -0,0 +1,225 @@
+use super::{Cell, Detail, Step, Technique, cell_name, combinations, digits_name};
+use crate::sudoku::{House, Sudoku, bit, digits};
+
+/// `size` cells of a house that only hold `size` digits between them.
+/// Those digits can be removed from the rest of the house.
+pub fn naked_subset(sudoku: &Sudoku, size: usize) -> Option<Step> {
+    find_subset(sudoku, size, false)
+}
+
+/// `size` digits of a house that only fit in `size` cells.
+/// Every other digit can be removed from those cells.
+pub fn hidden_subset(sudoku: &Sudoku, size: usize) -> Option<Step> {
+    find_subset(sudoku, size, true)
+}
+
+fn find_subset(sudoku: &Sudoku, size: usize, hidden: bool) -> Option<Step> {
+    let board = sudoku.get_board();
+    let candidates = sudoku.get_candidates();
+    for house in House::all() {
+        let empty: Vec<Cell> = house
+            .cells()
+            .into_iter()
+            .filter(|&(r, c)| board[r][c] == 0)
+            .collect();
+        // A subset needs at least one cell or digit left over to make eliminations
+        if empty.len() <= size {
+            continue;
+        }
+        let step = if hidden {
+            hidden_in_house(&candidates, house, &empty, size)
+        } else {
+            naked_in_house(&candidates, house, &empty, size)
+        };
+        if step.is_some() {
+            return step;
+        }
+    }
+    None
+}
+
+fn naked_in_house(
+    candidates: &[[u16; 9]; 9],
+    house: House,
+    empty: &[Cell],
+    size: usize,
+) -> Option<Step> {
+    let small: Vec<Cell> = empty
+        .iter()
+        .copied()
+        .filter(|&(r, c)| (2..=size as u32).contains(&candidates[r][c].count_ones()))
+        .collect();
+    for cells in combinations(&small, size) {
+        let mask = cells.iter().fold(0, |m, &(r, c)| m | candidates[r][c]);
+        if mask.count_ones() as usize != size {
+            continue;
+        }
+        let eliminations: Vec<(Cell, usize)> = empty
+            .iter()
+            .filter(|cell| !cells.contains(cell))
+            .flat_map(|&(r, c)| digits(candidates[r][c] & mask).map(move |num| ((r, c), num)))
+            .collect();
+        if eliminations.is_empty() {
+            continue;
+        }
+        let technique = match size {
+            2 => Technique::NakedPair,
+            3 => Technique::NakedTriple,
+            _ => Technique::NakedQuad,
+        };
+        return Some(subset_step(
+            technique,
+            candidates,
+            house,
+            cells,
+            mask,
+            eliminations,
+        ));
+    }
+    None
+}
+
+fn hidden_in_house(
+    candidates: &[[u16; 9]; 9],
+    house: House,
+    empty: &[Cell],
+    size: usize,
+) -> Option<Step> {
+    let spots = |num: usize| -> Vec<Cell> {
+        empty
+            .iter()
+            .copied()
+            .filter(|&(r, c)| candidates[r][c] & bit(num) != 0)
+            .collect()
+    };
+    let open: Vec<usize> = (1..=9)
+        .filter(|&num| (1..=size).contains(&spots(num).len()))
+        .collect();
+    for nums in combinations(&open, size) {
+        let mut cells: Vec<Cell> = nums.iter().flat_map(|&num| spots(num)).collect();
+        cells.sort();
+        cells.dedup();
+        if cells.len() != size {
+            continue;
+        }
+        let mask = nums.iter().fold(0, |m, &num| m | bit(num));
+        let eliminations: Vec<(Cell, usize)> = cells
+            .iter()
+            .flat_map(|&(r, c)| digits(candidates[r][c] & !mask).map(move |num| ((r, c), num)))
+            .collect();
+        if eliminations.is_empty() {
+            continue;
+        }
+        let technique = match size {
+            2 => Technique::HiddenPair,
+            3 => Technique::HiddenTriple,
+            _ => Technique::HiddenQuad,
+        };
+        return Some(subset_step(
+            technique,
+            candidates,
+            house,
+            cells,
+            mask,
+            eliminations,
+        ));
+    }
+    None
+}
+
+fn subset_step(
+    technique: Technique,
+    candidates: &[[u16; 9]; 9],
+    house: House,
+    cells: Vec<Cell>,
+    mask: u16,
+    eliminations: Vec<(Cell, usize)>,
+) -> Step {
+    let names = cells.iter().map(|&c| cell_name(c)).collect::<Vec<_>>();
+    Step {
+        technique,
+        placements: vec![],
+        eliminations,
+        highlights: cells
+            .iter()
+            .map(|&(r, c)| ((r, c), candidates[r][c] & mask))
+            .collect(),
+        explanation: format!(
+            "{} {} in {} of {}",
+            technique.name(),
+            digits_name(mask),
+            names.join(","),
+            house
+        ),
+        detail: Detail::Subset {
+            house,
+            cells,
+            digits: mask,
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::strategies::test_util::keep;
+
+    #[test]
+    fn test_naked_pair() {
+        let mut sudoku = Sudoku::new();
+        keep(&mut sudoku, (0, 0), &[1, 2]);
+        keep(&mut sudoku, (0, 4), &[1, 2]);
+        let step = naked_subset(&sudoku, 2).unwrap();
+        assert_eq!(step.technique, Technique::NakedPair);
+        assert_eq!(
+            step.detail,
+            Detail::Subset {
+                house: House::Row(0),
+                cells: vec![(0, 0), (0, 4)],
+                digits: 0b11,
+            }
+        );
+        assert_eq!(step.eliminations.len(), 14);
+        assert!(step.eliminations.contains(&((0, 8), 2)));
+        assert_eq!(step.explanation, "Naked Pair {1,2} in r1c1,r1c5 of row 1");
+    }
+    #[test]
+    fn test_naked_triple_with_partial_cells() {
+        let mut sudoku = Sudoku::new();
+        keep(&mut sudoku, (3, 3), &[4, 5]);
+        keep(&mut sudoku, (4, 4), &[5, 6]);
+        keep(&mut sudoku, (5, 5), &[4, 6]);
+        assert!(naked_subset(&sudoku, 2).is_none());
+        let step = naked_subset(&sudoku, 3).unwrap();
+        assert_eq!(step.technique, Technique::NakedTriple);
+        assert!(matches!(
+            step.detail,
+            Detail::Subset {
+                house: House::Box(4),
+                ..
+            }
+        ));
+        assert_eq!(step.eliminations.len(), 18);
+    }
+    #[test]
+    fn test_hidden_pair() {
+        let mut sudoku = Sudoku::new();
+        for col in 2..9 {
+            sudoku.eliminate_candidate(8, col, 3);
+            sudoku.eliminate_candidate(8, col, 7);
+        }
+        assert!(hidden_subset(&sudoku, 3).is_none());
+        let step = hidden_subset(&sudoku, 2).unwrap();
+        assert_eq!(step.technique, Technique::HiddenPair);
+        assert_eq!(
+            step.detail,
+            Detail::Subset {
+                house: House::Row(8),
+                cells: vec![(8, 0), (8, 1)],
+                digits: bit(3) | bit(7),
+            }
+        );
+        assert_eq!(step.eliminations.len(), 14);
+        assert!(!step.eliminations.contains(&((8, 0), 3)));
+    }
+}
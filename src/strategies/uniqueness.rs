@@ -0,0 +1,452 @@
+use super::{Cell, Detail, Step, Technique, cell_name, combinations, digits_name, seen_by_all};
+use crate::sudoku::{House, Sudoku, bit, box_of, digits};
+
+/// Four unsolved cells spanning two rows, two columns and two boxes that all hold the same
+/// pair of digits. Cells are ordered top left, top right, bottom left, bottom right so the
+/// opposite corner of `i` is `3 - i`.
+struct Rectangle {
+    cells: [Cell; 4],
+    pair: u16,
+}
+
+impl Rectangle {
+    /// Candidates of each corner beyond the pair
+    fn extras(&self, candidates: &[[u16; 9]; 9]) -> [u16; 4] {
+        self.cells.map(|(r, c)| candidates[r][c] & !self.pair)
+    }
+    /// Corners with extra candidates, the rest only hold the pair
+    fn roof(&self, candidates: &[[u16; 9]; 9]) -> Vec<usize> {
+        let extras = self.extras(candidates);
+        (0..4).filter(|&i| extras[i] != 0).collect()
+    }
+    /// Houses holding both corners `a` and `b`
+    fn shared_houses(&self, a: usize, b: usize) -> Vec<House> {
+        let (p, q) = (self.cells[a], self.cells[b]);
+        let mut houses = vec![];
+        if p.0 == q.0 {
+            houses.push(House::Row(p.0));
+        }
+        if p.1 == q.1 {
+            houses.push(House::Column(p.1));
+        }
+        if box_of(p.0, p.1) == box_of(q.0, q.1) {
+            houses.push(House::Box(box_of(p.0, p.1)));
+        }
+        houses
+    }
+}
+
+/// Eliminations of one unique rectangle type for a rectangle
+type RectangleType = fn(&Rectangle, &[[u16; 9]; 9]) -> Vec<(Cell, usize)>;
+
+fn rectangles(candidates: &[[u16; 9]; 9]) -> Vec<Rectangle> {
+    let mut rectangles = vec![];
+    for r1 in 0..9 {
+        for r2 in r1 + 1..9 {
+            for c1 in 0..9 {
+                for c2 in c1 + 1..9 {
+                    let cells = [(r1, c1), (r1, c2), (r2, c1), (r2, c2)];
+                    let mut boxes: Vec<usize> = cells.iter().map(|&(r, c)| box_of(r, c)).collect();
+                    boxes.sort();
+                    boxes.dedup();
+                    if boxes.len() != 2 {
+                        continue;
+                    }
+                    let common = cells
+                        .iter()
+                        .fold(u16::MAX, |mask, &(r, c)| mask & candidates[r][c]);
+                    for pair in combinations(&digits(common).collect::<Vec<_>>(), 2) {
+                        rectangles.push(Rectangle {
+                            cells,
+                            pair: bit(pair[0]) | bit(pair[1]),
+                        });
+                    }
+                }
+            }
+        }
+    }
+    rectangles
+}
+
+/// Unique rectangles of type 1 to 6. Filling all four corners with only the pair would let
+/// the two digits swap, so a puzzle with one solution must use an extra candidate somewhere.
+/// Only valid when the sudoku is known to have a unique solution.
+pub fn unique_rectangle(sudoku: &Sudoku, kind: usize) -> Option<Step> {
+    if !sudoku.has_unique_solution() {
+        return None;
+    }
+    let candidates = sudoku.get_candidates();
+    let (technique, find): (Technique, RectangleType) = match kind {
+        1 => (Technique::UniqueRectangle1, type_1),
+        2 => (Technique::UniqueRectangle2, type_2),
+        3 => (Technique::UniqueRectangle3, type_3),
+        4 => (Technique::UniqueRectangle4, type_4),
+        5 => (Technique::UniqueRectangle5, type_5),
+        6 => (Technique::UniqueRectangle6, type_6),
+        _ => return None,
+    };
+    rectangles(&candidates)
+        .iter()
+        .find_map(|rect| rectangle_step(technique, rect, find(rect, &candidates)))
+}
+
+/// Only one corner has extras, so it cannot be either digit of the pair
+fn type_1(rect: &Rectangle, candidates: &[[u16; 9]; 9]) -> Vec<(Cell, usize)> {
+    let roof = rect.roof(candidates);
+    let [corner] = roof[..] else {
+        return vec![];
+    };
+    digits(rect.pair)
+        .map(|num| (rect.cells[corner], num))
+        .collect()
+}
+
+/// Two corners in one house share a single extra digit, which must be in one of them
+fn type_2(rect: &Rectangle, candidates: &[[u16; 9]; 9]) -> Vec<(Cell, usize)> {
+    let roof = rect.roof(candidates);
+    if roof.len() != 2 || roof[0] + roof[1] == 3 {
+        return vec![];
+    }
+    shared_extra(rect, candidates, &roof)
+}
+
+/// The extras of two corners in one house act as one cell, forming a naked subset with other
+/// cells of that house
+fn type_3(rect: &Rectangle, candidates: &[[u16; 9]; 9]) -> Vec<(Cell, usize)> {
+    let roof = rect.roof(candidates);
+    if roof.len() != 2 || roof[0] + roof[1] == 3 {
+        return vec![];
+    }
+    let extras = rect.extras(candidates);
+    let virtual_cell = extras[roof[0]] | extras[roof[1]];
+    if virtual_cell.count_ones() < 2 {
+        return vec![];
+    }
+    for house in rect.shared_houses(roof[0], roof[1]) {
+        let others: Vec<Cell> = house
+            .cells()
+            .into_iter()
+            .filter(|&(r, c)| candidates[r][c] != 0 && !rect.cells.contains(&(r, c)))
+            .collect();
+        for size in 1..=others.len().min(3) {
+            for subset in combinations(&others, size) {
+                let mask = subset
+                    .iter()
+                    .fold(virtual_cell, |mask, &(r, c)| mask | candidates[r][c]);
+                if mask.count_ones() as usize != size + 1 {
+                    continue;
+                }
+                let eliminations: Vec<(Cell, usize)> = others
+                    .iter()
+                    .filter(|cell| !subset.contains(cell))
+                    .flat_map(|&(r, c)| digits(candidates[r][c] & mask).map(move |n| ((r, c), n)))
+                    .collect();
+                if !eliminations.is_empty() {
+                    return eliminations;
+                }
+            }
+        }
+    }
+    vec![]
+}
+
+/// Two corners in one house hold one digit of the pair as a strong link, so the other digit
+/// of the pair goes from both
+fn type_4(rect: &Rectangle, candidates: &[[u16; 9]; 9]) -> Vec<(Cell, usize)> {
+    let roof = rect.roof(candidates);
+    if roof.len() != 2 || roof[0] + roof[1] == 3 {
+        return vec![];
+    }
+    let (a, b) = (rect.cells[roof[0]], rect.cells[roof[1]]);
+    for house in rect.shared_houses(roof[0], roof[1]) {
+        for num in digits(rect.pair) {
+            if holders(candidates, house, num) == [a, b] {
+                let other = digits(rect.pair & !bit(num)).next().unwrap();
+                return vec![(a, other), (b, other)];
+            }
+        }
+    }
+    vec![]
+}
+
+/// Two opposite corners, or three corners, share a single extra digit
+fn type_5(rect: &Rectangle, candidates: &[[u16; 9]; 9]) -> Vec<(Cell, usize)> {
+    let roof = rect.roof(candidates);
+    if !(roof.len() == 3 || roof.len() == 2 && roof[0] + roof[1] == 3) {
+        return vec![];
+    }
+    shared_extra(rect, candidates, &roof)
+}
+
+/// The pair sits in two opposite corners and one of its digits is locked to the rectangle in
+/// both rows or both columns. That digit in another corner would force the deadly pattern.
+fn type_6(rect: &Rectangle, candidates: &[[u16; 9]; 9]) -> Vec<(Cell, usize)> {
+    let roof = rect.roof(candidates);
+    if roof.len() != 2 || roof[0] + roof[1] != 3 {
+        return vec![];
+    }
+    let [(r1, c1), _, _, (r2, c2)] = rect.cells;
+    for num in digits(rect.pair) {
+        let locked = |houses: [House; 2]| {
+            houses.iter().all(|&house| {
+                holders(candidates, house, num)
+                    .iter()
+                    .all(|cell| rect.cells.contains(cell))
+            })
+        };
+        if locked([House::Row(r1), House::Row(r2)])
+            || locked([House::Column(c1), House::Column(c2)])
+        {
+            return roof.iter().map(|&i| (rect.cells[i], num)).collect();
+        }
+    }
+    vec![]
+}
+
+/// One corner only holds the pair and one digit of the pair is locked to the rectangle in
+/// both the row and column of the opposite corner, so that corner cannot be the other digit.
+/// Only valid when the sudoku is known to have a unique solution.
+pub fn hidden_rectangle(sudoku: &Sudoku) -> Option<Step> {
+    if !sudoku.has_unique_solution() {
+        return None;
+    }
+    let candidates = sudoku.get_candidates();
+    rectangles(&candidates).iter().find_map(|rect| {
+        let extras = rect.extras(&candidates);
+        for corner in (0..4).filter(|&i| extras[i] == 0) {
+            let opposite = rect.cells[3 - corner];
+            if extras[3 - corner] == 0 {
+                continue;
+            }
+            for num in digits(rect.pair) {
+                let locked = [House::Row(opposite.0), House::Column(opposite.1)]
+                    .iter()
+                    .all(|&house| {
+                        holders(&candidates, house, num)
+                            .iter()
+                            .all(|cell| rect.cells.contains(cell))
+                    });
+                if locked {
+                    let other = digits(rect.pair & !bit(num)).next().unwrap();
+                    return rectangle_step(
+                        Technique::HiddenRectangle,
+                        rect,
+                        vec![(opposite, other)],
+                    );
+                }
+            }
+        }
+        None
+    })
+}
+
+/// Every unsolved cell is bivalue except one with three candidates. Without the digit that
+/// appears three times in its houses the grid would be a BUG with two solutions or none, so
+/// that digit goes in the cell.
+/// Only valid when the sudoku is known to have a unique solution.
+pub fn bug_plus_one(sudoku: &Sudoku) -> Option<Step> {
+    if !sudoku.has_unique_solution() {
+        return None;
+    }
+    let mut candidates = sudoku.get_candidates();
+    let unsolved: Vec<Cell> = (0..81)
+        .map(|i| (i / 9, i % 9))
+        .filter(|&(r, c)| candidates[r][c] != 0)
+        .collect();
+    let mut trivalue = unsolved
+        .iter()
+        .filter(|&&(r, c)| candidates[r][c].count_ones() != 2);
+    let &(row, col) = trivalue.next()?;
+    if trivalue.next().is_some() || candidates[row][col].count_ones() != 3 {
+        return None;
+    }
+    let mask = candidates[row][col];
+    for num in digits(mask) {
+        candidates[row][col] = mask & !bit(num);
+        let bug = House::all()
+            .all(|house| (1..=9).all(|n| matches!(holders(&candidates, house, n).len(), 0 | 2)));
+        if bug {
+            return Some(Step {
+                technique: Technique::BugPlusOne,
+                placements: vec![((row, col), num)],
+                eliminations: vec![],
+                highlights: vec![((row, col), bit(num))],
+                explanation: format!(
+                    "{}: every other cell is bivalue, so {} must be {}",
+                    Technique::BugPlusOne.name(),
+                    cell_name((row, col)),
+                    num
+                ),
+                detail: Detail::None,
+            });
+        }
+    }
+    None
+}
+
+fn holders(candidates: &[[u16; 9]; 9], house: House, num: usize) -> Vec<Cell> {
+    house
+        .cells()
+        .into_iter()
+        .filter(|&(r, c)| candidates[r][c] & bit(num) != 0)
+        .collect()
+}
+
+/// Removes the single extra digit shared by every roof corner from the cells that see them all
+fn shared_extra(
+    rect: &Rectangle,
+    candidates: &[[u16; 9]; 9],
+    roof: &[usize],
+) -> Vec<(Cell, usize)> {
+    let extras = rect.extras(candidates);
+    let extra = extras[roof[0]];
+    if extra.count_ones() != 1 || roof.iter().any(|&i| extras[i] != extra) {
+        return vec![];
+    }
+    let num = digits(extra).next().unwrap();
+    let corners: Vec<Cell> = roof.iter().map(|&i| rect.cells[i]).collect();
+    seen_by_all(&corners)
+        .into_iter()
+        .filter(|&(r, c)| candidates[r][c] & extra != 0)
+        .map(|cell| (cell, num))
+        .collect()
+}
+
+fn rectangle_step(
+    technique: Technique,
+    rect: &Rectangle,
+    mut eliminations: Vec<(Cell, usize)>,
+) -> Option<Step> {
+    if eliminations.is_empty() {
+        return None;
+    }
+    eliminations.sort();
+    Some(Step {
+        technique,
+        placements: vec![],
+        eliminations,
+        highlights: rect.cells.iter().map(|&cell| (cell, rect.pair)).collect(),
+        explanation: format!(
+            "{} {} in {}",
+            technique.name(),
+            digits_name(rect.pair),
+            rect.cells
+                .iter()
+                .map(|&cell| cell_name(cell))
+                .collect::<Vec<_>>()
+                .join(",")
+        ),
+        detail: Detail::Rectangle {
+            digits: rect.pair,
+            cells: rect.cells.to_vec(),
+        },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::strategies::test_util::{keep, restrict};
+
+    fn unique() -> Sudoku {
+        let mut sudoku = Sudoku::new();
+        sudoku.set_unique_solution(true);
+        sudoku
+    }
+
+    #[test]
+    fn test_needs_unique_solution() {
+        let mut sudoku = Sudoku::new();
+        keep(&mut sudoku, (0, 0), &[1, 2]);
+        keep(&mut sudoku, (0, 3), &[1, 2]);
+        keep(&mut sudoku, (1, 0), &[1, 2]);
+        assert!(unique_rectangle(&sudoku, 1).is_none());
+        sudoku.set_unique_solution(true);
+        assert!(unique_rectangle(&sudoku, 1).is_some());
+    }
+    #[test]
+    fn test_type_1() {
+        let mut sudoku = unique();
+        keep(&mut sudoku, (0, 0), &[1, 2]);
+        keep(&mut sudoku, (0, 3), &[1, 2]);
+        keep(&mut sudoku, (1, 0), &[1, 2]);
+        keep(&mut sudoku, (1, 3), &[1, 2, 5]);
+        let step = unique_rectangle(&sudoku, 1).unwrap();
+        assert_eq!(step.eliminations, vec![((1, 3), 1), ((1, 3), 2)]);
+        assert_eq!(
+            step.explanation,
+            "Unique Rectangle Type 1 {1,2} in r1c1,r1c4,r2c1,r2c4"
+        );
+    }
+    #[test]
+    fn test_type_2() {
+        let mut sudoku = unique();
+        keep(&mut sudoku, (0, 0), &[1, 2]);
+        keep(&mut sudoku, (1, 0), &[1, 2]);
+        keep(&mut sudoku, (0, 3), &[1, 2, 5]);
+        keep(&mut sudoku, (1, 3), &[1, 2, 5]);
+        let step = unique_rectangle(&sudoku, 2).unwrap();
+        assert!(step.eliminations.contains(&((5, 3), 5)));
+        assert!(step.eliminations.contains(&((2, 4), 5)));
+        assert!(!step.eliminations.contains(&((0, 4), 1)));
+    }
+    #[test]
+    fn test_type_3() {
+        let mut sudoku = unique();
+        keep(&mut sudoku, (0, 0), &[1, 2]);
+        keep(&mut sudoku, (1, 0), &[1, 2]);
+        keep(&mut sudoku, (0, 3), &[1, 2, 3]);
+        keep(&mut sudoku, (1, 3), &[1, 2, 4]);
+        keep(&mut sudoku, (5, 3), &[3, 4]);
+        let step = unique_rectangle(&sudoku, 3).unwrap();
+        assert!(step.eliminations.contains(&((8, 3), 3)));
+        assert!(step.eliminations.contains(&((8, 3), 4)));
+        assert!(!step.eliminations.iter().any(|e| e.0 == (5, 3)));
+    }
+    #[test]
+    fn test_type_4() {
+        let mut sudoku = unique();
+        keep(&mut sudoku, (0, 0), &[1, 2]);
+        keep(&mut sudoku, (1, 0), &[1, 2]);
+        keep(&mut sudoku, (0, 3), &[1, 2, 5]);
+        keep(&mut sudoku, (1, 3), &[1, 2, 6]);
+        restrict(&mut sudoku, House::Column(3), 1, &[(0, 3), (1, 3)]);
+        let step = unique_rectangle(&sudoku, 4).unwrap();
+        assert_eq!(step.eliminations, vec![((0, 3), 2), ((1, 3), 2)]);
+    }
+    #[test]
+    fn test_type_5() {
+        let mut sudoku = unique();
+        keep(&mut sudoku, (0, 0), &[1, 2, 5]);
+        keep(&mut sudoku, (0, 3), &[1, 2]);
+        keep(&mut sudoku, (1, 0), &[1, 2]);
+        keep(&mut sudoku, (1, 3), &[1, 2, 5]);
+        let step = unique_rectangle(&sudoku, 5).unwrap();
+        assert_eq!(
+            step.eliminations,
+            vec![((0, 4), 5), ((0, 5), 5), ((1, 1), 5), ((1, 2), 5)]
+        );
+    }
+    #[test]
+    fn test_type_6() {
+        let mut sudoku = unique();
+        keep(&mut sudoku, (0, 0), &[1, 2]);
+        keep(&mut sudoku, (1, 3), &[1, 2]);
+        keep(&mut sudoku, (0, 3), &[1, 2, 5]);
+        keep(&mut sudoku, (1, 0), &[1, 2, 6]);
+        restrict(&mut sudoku, House::Row(0), 1, &[(0, 0), (0, 3)]);
+        restrict(&mut sudoku, House::Row(1), 1, &[(1, 0), (1, 3)]);
+        let step = unique_rectangle(&sudoku, 6).unwrap();
+        assert_eq!(step.eliminations, vec![((0, 3), 1), ((1, 0), 1)]);
+    }
+    #[test]
+    fn test_hidden_rectangle() {
+        let mut sudoku = unique();
+        keep(&mut sudoku, (0, 0), &[1, 2]);
+        keep(&mut sudoku, (1, 3), &[1, 2, 5]);
+        restrict(&mut sudoku, House::Row(1), 2, &[(1, 0), (1, 3)]);
+        restrict(&mut sudoku, House::Column(3), 2, &[(0, 3), (1, 3)]);
+        let step = hidden_rectangle(&sudoku).unwrap();
+        assert_eq!(step.eliminations, vec![((1, 3), 1)]);
+    }
+}
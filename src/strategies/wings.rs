@@ -0,0 +1,265 @@
+use super::{Cell, Detail, Step, Technique, cell_name, seen_by_all, sees};
+use crate::sudoku::{House, Sudoku, bit, digits};
+
+/// A bivalue pivot {x,y} that sees two bivalue pincers {x,z} and {y,z}.
+/// Whichever value the pivot takes one of the pincers is z, so z goes from every cell that
+/// sees both pincers.
+pub fn xy_wing(sudoku: &Sudoku) -> Option<Step> {
+    let candidates = sudoku.get_candidates();
+    let bivalue = bivalue_cells(&candidates);
+    for &pivot in &bivalue {
+        let pivot_mask = candidates[pivot.0][pivot.1];
+        let pincers: Vec<Cell> = bivalue
+            .iter()
+            .copied()
+            .filter(|&cell| sees(cell, pivot))
+            .filter(|&(r, c)| (candidates[r][c] & pivot_mask).count_ones() == 1)
+            .collect();
+        for (i, &a) in pincers.iter().enumerate() {
+            for &b in &pincers[i + 1..] {
+                let (mask_a, mask_b) = (candidates[a.0][a.1], candidates[b.0][b.1]);
+                let z = mask_a & mask_b & !pivot_mask;
+                // The pincers must pick up different halves of the pivot
+                if z.count_ones() != 1 || mask_a & mask_b & pivot_mask != 0 {
+                    continue;
+                }
+                if let Some(step) = wing_step(
+                    Technique::XYWing,
+                    &candidates,
+                    Some(pivot),
+                    None,
+                    vec![a, b],
+                    z,
+                ) {
+                    return Some(step);
+                }
+            }
+        }
+    }
+    None
+}
+
+/// A pivot {x,y,z} that sees two bivalue pincers {x,z} and {y,z}.
+/// One of the three cells must be z, so z goes from every cell that sees all of them.
+pub fn xyz_wing(sudoku: &Sudoku) -> Option<Step> {
+    let candidates = sudoku.get_candidates();
+    let bivalue = bivalue_cells(&candidates);
+    for row in 0..9 {
+        for col in 0..9 {
+            let pivot_mask = candidates[row][col];
+            if pivot_mask.count_ones() != 3 {
+                continue;
+            }
+            let pincers: Vec<Cell> = bivalue
+                .iter()
+                .copied()
+                .filter(|&cell| sees(cell, (row, col)))
+                .filter(|&(r, c)| candidates[r][c] & !pivot_mask == 0)
+                .collect();
+            for (i, &a) in pincers.iter().enumerate() {
+                for &b in &pincers[i + 1..] {
+                    let z = candidates[a.0][a.1] & candidates[b.0][b.1];
+                    if z.count_ones() != 1 {
+                        continue;
+                    }
+                    if let Some(step) = wing_step(
+                        Technique::XYZWing,
+                        &candidates,
+                        Some((row, col)),
+                        None,
+                        vec![a, b],
+                        z,
+                    ) {
+                        return Some(step);
+                    }
+                }
+            }
+        }
+    }
+    None
+}
+
+/// Two bivalue cells {x,y} that do not see each other, joined by a strong link on x.
+/// One of the ends of the link is x, so one of the cells is y and y goes from every cell
+/// that sees both of them.
+pub fn w_wing(sudoku: &Sudoku) -> Option<Step> {
+    let candidates = sudoku.get_candidates();
+    let bivalue = bivalue_cells(&candidates);
+    for (i, &a) in bivalue.iter().enumerate() {
+        let mask = candidates[a.0][a.1];
+        for &b in &bivalue[i + 1..] {
+            if candidates[b.0][b.1] != mask || sees(a, b) {
+                continue;
+            }
+            for x in digits(mask) {
+                let y = mask & !bit(x);
+                for house in House::all() {
+                    let ends: Vec<Cell> = house
+                        .cells()
+                        .into_iter()
+                        .filter(|&(r, c)| candidates[r][c] & bit(x) != 0)
+                        .collect();
+                    let [p, q] = ends[..] else {
+                        continue;
+                    };
+                    if [p, q].contains(&a) || [p, q].contains(&b) {
+                        continue;
+                    }
+                    let link = if sees(p, a) && sees(q, b) {
+                        (p, q)
+                    } else if sees(q, a) && sees(p, b) {
+                        (q, p)
+                    } else {
+                        continue;
+                    };
+                    if let Some(step) = wing_step(
+                        Technique::WWing,
+                        &candidates,
+                        None,
+                        Some(link),
+                        vec![a, b],
+                        y,
+                    ) {
+                        return Some(step);
+                    }
+                }
+            }
+        }
+    }
+    None
+}
+
+fn bivalue_cells(candidates: &[[u16; 9]; 9]) -> Vec<Cell> {
+    (0..81)
+        .map(|i| (i / 9, i % 9))
+        .filter(|&(r, c)| candidates[r][c].count_ones() == 2)
+        .collect()
+}
+
+/// Builds the step once the pattern is found, or `None` when nothing sees every cell that
+/// could hold `z`
+fn wing_step(
+    technique: Technique,
+    candidates: &[[u16; 9]; 9],
+    pivot: Option<Cell>,
+    link: Option<(Cell, Cell)>,
+    pincers: Vec<Cell>,
+    z: u16,
+) -> Option<Step> {
+    let num = digits(z).next()?;
+    let mut holders = pincers.clone();
+    if let Some(pivot) = pivot
+        && candidates[pivot.0][pivot.1] & z != 0
+    {
+        holders.push(pivot);
+    }
+    let eliminations: Vec<(Cell, usize)> = seen_by_all(&holders)
+        .into_iter()
+        .filter(|&(r, c)| candidates[r][c] & z != 0)
+        .map(|cell| (cell, num))
+        .collect();
+    if eliminations.is_empty() {
+        return None;
+    }
+    let mut highlights: Vec<(Cell, u16)> = pincers
+        .iter()
+        .map(|&(r, c)| ((r, c), candidates[r][c]))
+        .collect();
+    let joint = match (pivot, link) {
+        (Some(pivot), _) => {
+            highlights.push((pivot, candidates[pivot.0][pivot.1]));
+            format!("pivot {}", cell_name(pivot))
+        }
+        (None, Some((p, q))) => {
+            let x = candidates[pincers[0].0][pincers[0].1] & !z;
+            highlights.extend([(p, x), (q, x)]);
+            format!("strong link {}={}", cell_name(p), cell_name(q))
+        }
+        (None, None) => String::new(),
+    };
+    Some(Step {
+        technique,
+        placements: vec![],
+        eliminations,
+        highlights,
+        explanation: format!(
+            "{}: {} with pincers {} removes {}",
+            technique.name(),
+            joint,
+            pincers
+                .iter()
+                .map(|&cell| cell_name(cell))
+                .collect::<Vec<_>>()
+                .join(","),
+            num
+        ),
+        detail: Detail::Wing {
+            digit: num,
+            pivot,
+            link,
+            pincers,
+        },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::strategies::test_util::keep;
+
+    #[test]
+    fn test_xy_wing() {
+        let mut sudoku = Sudoku::new();
+        keep(&mut sudoku, (0, 0), &[1, 2]);
+        keep(&mut sudoku, (0, 5), &[1, 3]);
+        keep(&mut sudoku, (4, 0), &[2, 3]);
+        let step = xy_wing(&sudoku).unwrap();
+        assert_eq!(
+            step.detail,
+            Detail::Wing {
+                digit: 3,
+                pivot: Some((0, 0)),
+                link: None,
+                pincers: vec![(0, 5), (4, 0)],
+            }
+        );
+        assert_eq!(step.eliminations, vec![((4, 5), 3)]);
+        assert_eq!(
+            step.explanation,
+            "XY-Wing: pivot r1c1 with pincers r1c6,r5c1 removes 3"
+        );
+    }
+    #[test]
+    fn test_xyz_wing() {
+        let mut sudoku = Sudoku::new();
+        keep(&mut sudoku, (0, 0), &[1, 2, 3]);
+        keep(&mut sudoku, (0, 5), &[1, 3]);
+        keep(&mut sudoku, (1, 1), &[2, 3]);
+        assert!(xy_wing(&sudoku).is_none());
+        let step = xyz_wing(&sudoku).unwrap();
+        assert_eq!(step.technique, Technique::XYZWing);
+        // Only the rest of row 1 inside box 1 sees all three cells
+        assert_eq!(step.eliminations, vec![((0, 1), 3), ((0, 2), 3)]);
+    }
+    #[test]
+    fn test_w_wing() {
+        let mut sudoku = Sudoku::new();
+        keep(&mut sudoku, (0, 0), &[4, 7]);
+        keep(&mut sudoku, (8, 8), &[4, 7]);
+        // Column 5 only holds 4 in r1c5 and r9c5
+        for row in 1..8 {
+            sudoku.eliminate_candidate(row, 4, 4);
+        }
+        let step = w_wing(&sudoku).unwrap();
+        assert_eq!(
+            step.detail,
+            Detail::Wing {
+                digit: 7,
+                pivot: None,
+                link: Some(((0, 4), (8, 4))),
+                pincers: vec![(0, 0), (8, 8)],
+            }
+        );
+        assert_eq!(step.eliminations, vec![((0, 8), 7), ((8, 0), 7)]);
+    }
+}
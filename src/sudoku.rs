@@ -1,4 +1,9 @@
-#[derive(Clone)]
+use crate::solver;
+
+/// Candidate bitmask with every digit available, bit `n - 1` represents digit `n`
+pub const ALL_CANDIDATES: u16 = 0b111111111;
+
+#[derive(Debug, Clone)]
 pub struct Sudoku {
     /// Representation of the sudoku board
     /// Option not needed since we want to
@@ -12,12 +17,106 @@ pub struct Sudoku {
     solution: Option<[[usize; 9]; 9]>,
     /// Optional Enum to track what difficulty the board is at
     difficulty: Option<Difficulty>,
+    /// Numeric rating of the puzzle, on the Sudoku Explainer scale where 1.5 is a hidden
+    /// single and anything above 9 needs more than forcing chains
+    rating: Option<f32>,
+    /// Pencil marks for every cell, bit `n - 1` is set when `n` can still go in the cell.
+    /// Filled cells have no candidates.
+    candidates: [[u16; 9]; 9],
+    /// Whether the puzzle is known to have exactly one solution, which uniqueness based
+    /// strategies rely on
+    unique: bool,
+}
+/// A row, column or box, indexed from 0 to 8. Boxes are numbered left to right, top to bottom.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum House {
+    Row(usize),
+    Column(usize),
+    Box(usize),
+}
+
+impl House {
+    /// All 27 houses: rows, then columns, then boxes
+    pub fn all() -> impl Iterator<Item = House> {
+        (0..9)
+            .map(House::Row)
+            .chain((0..9).map(House::Column))
+            .chain((0..9).map(House::Box))
+    }
+    pub fn cells(&self) -> [(usize, usize); 9] {
+        std::array::from_fn(|i| match *self {
+            House::Row(row) => (row, i),
+            House::Column(col) => (i, col),
+            House::Box(b) => (b / 3 * 3 + i / 3, b % 3 * 3 + i % 3),
+        })
+    }
+    pub fn contains(&self, (row, col): (usize, usize)) -> bool {
+        match *self {
+            House::Row(r) => r == row,
+            House::Column(c) => c == col,
+            House::Box(b) => box_of(row, col) == b,
+        }
+    }
+}
+
+impl std::fmt::Display for House {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            House::Row(row) => write!(f, "row {}", row + 1),
+            House::Column(col) => write!(f, "column {}", col + 1),
+            House::Box(b) => write!(f, "box {}", b + 1),
+        }
+    }
 }
+
+/// Grades from hidden singles only up to puzzles that need forcing chains
 #[derive(Debug, Clone, PartialEq)]
 pub enum Difficulty {
+    Beginner,
     Easy,
     Medium,
     Hard,
+    Expert,
+    Diabolical,
+    Extreme,
+}
+
+impl Difficulty {
+    /// Every grade, easiest first
+    pub fn all() -> [Difficulty; 7] {
+        [
+            Difficulty::Beginner,
+            Difficulty::Easy,
+            Difficulty::Medium,
+            Difficulty::Hard,
+            Difficulty::Expert,
+            Difficulty::Diabolical,
+            Difficulty::Extreme,
+        ]
+    }
+}
+
+impl std::fmt::Display for Difficulty {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Difficulty::Beginner => "Beginner",
+            Difficulty::Easy => "Easy",
+            Difficulty::Medium => "Medium",
+            Difficulty::Hard => "Hard",
+            Difficulty::Expert => "Expert",
+            Difficulty::Diabolical => "Diabolical",
+            Difficulty::Extreme => "Extreme",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// How many solutions a board has, which tells whether it makes a proper puzzle
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Uniqueness {
+    NoSolution,
+    Unique,
+    Multiple,
 }
 
 impl Sudoku {
@@ -27,6 +126,9 @@ impl Sudoku {
             puzzle: None,
             solution: None,
             difficulty: None,
+            rating: None,
+            candidates: [[ALL_CANDIDATES; 9]; 9],
+            unique: false,
         }
     }
     pub fn get_board(&self) -> [[usize; 9]; 9] {
@@ -34,6 +136,7 @@ impl Sudoku {
     }
     pub fn set_board(&mut self, board: [[usize; 9]; 9]) {
         self.board = board;
+        self.compute_candidates();
     }
     pub fn get_puzzle(&self) -> Option<[[usize; 9]; 9]> {
         self.puzzle.clone()
@@ -46,15 +149,40 @@ impl Sudoku {
     }
     pub fn set_solution(&mut self, solution: [[usize; 9]; 9]) {
         self.solution = Some(solution);
+        self.unique = true;
+    }
+    pub fn has_unique_solution(&self) -> bool {
+        self.unique
+    }
+    pub fn set_unique_solution(&mut self, unique: bool) {
+        self.unique = unique;
     }
     pub fn clear_board(&mut self) {
         self.board = [[0; 9]; 9];
         self.puzzle = None;
         self.solution = None;
         self.difficulty = None;
+        self.rating = None;
+        self.candidates = [[ALL_CANDIDATES; 9]; 9];
+        self.unique = false;
     }
     pub fn update_cell(&mut self, row: usize, col: usize, num: usize) {
+        let previous = self.board[row][col];
         self.board[row][col] = num;
+        // The old digit may be possible again for the peers that only lost it to this cell
+        if previous > 0 && previous != num {
+            for (r, c) in peers(row, col) {
+                self.restore_candidate(r, c, previous);
+            }
+        }
+        if num > 0 {
+            self.candidates[row][col] = 0;
+            for (r, c) in peers(row, col) {
+                self.eliminate_candidate(r, c, num);
+            }
+        } else {
+            self.candidates[row][col] = self.allowed_digits(row, col);
+        }
     }
     pub fn get_difficulty(&self) -> Option<Difficulty> {
         self.difficulty.clone()
@@ -62,23 +190,161 @@ impl Sudoku {
     pub fn set_difficulty(&mut self, diff: Option<Difficulty>) {
         self.difficulty = diff;
     }
+    pub fn get_rating(&self) -> Option<f32> {
+        self.rating
+    }
+    pub fn set_rating(&mut self, rating: Option<f32>) {
+        self.rating = rating;
+    }
+    /// Number of solutions of the current board, counting no further than `limit`
+    pub fn count_solutions(&self, limit: usize) -> usize {
+        solver::count_solutions(self.board, limit)
+    }
+    /// Whether the board has no, one or several solutions, counted as an exact cover problem
+    pub fn uniqueness(&self) -> Uniqueness {
+        match solver::dlx::count(self.board, 2) {
+            0 => Uniqueness::NoSolution,
+            1 => Uniqueness::Unique,
+            _ => Uniqueness::Multiple,
+        }
+    }
     pub fn reset(&mut self) {
         if let Some(puzzle) = self.puzzle {
             self.board = puzzle;
+            self.compute_candidates();
         }
     }
+    pub fn get_candidates(&self) -> [[u16; 9]; 9] {
+        self.candidates
+    }
+    pub fn get_cell_candidates(&self, row: usize, col: usize) -> u16 {
+        self.candidates[row][col]
+    }
+    pub fn has_candidate(&self, row: usize, col: usize, num: usize) -> bool {
+        self.candidates[row][col] & bit(num) != 0
+    }
+    /// Rebuilds every pencil mark from the digits on the board.
+    /// Any candidates eliminated by hand or by a strategy are discarded.
+    pub fn compute_candidates(&mut self) {
+        for row in 0..9 {
+            for col in 0..9 {
+                self.candidates[row][col] = if self.board[row][col] == 0 {
+                    self.allowed_digits(row, col)
+                } else {
+                    0
+                };
+            }
+        }
+    }
+    /// Removes `num` from the cell's pencil marks, returning whether it was there.
+    pub fn eliminate_candidate(&mut self, row: usize, col: usize, num: usize) -> bool {
+        let present = self.has_candidate(row, col, num);
+        self.candidates[row][col] &= !bit(num);
+        present
+    }
+    /// Puts `num` back into the cell's pencil marks as long as the rules still allow it,
+    /// returning whether it was restored.
+    pub fn restore_candidate(&mut self, row: usize, col: usize, num: usize) -> bool {
+        if self.has_candidate(row, col, num) || self.allowed_digits(row, col) & bit(num) == 0 {
+            return false;
+        }
+        self.candidates[row][col] |= bit(num);
+        true
+    }
+    /// Digits that no filled peer of an empty cell already uses
+    fn allowed_digits(&self, row: usize, col: usize) -> u16 {
+        if self.board[row][col] != 0 {
+            return 0;
+        }
+        let mut mask = ALL_CANDIDATES;
+        for (r, c) in peers(row, col) {
+            mask &= !bit(self.board[r][c]);
+        }
+        mask
+    }
+    /// Filled cells sharing their digit with another cell of a row, column or box, in row
+    /// order
+    pub fn conflicts(&self) -> Vec<(usize, usize)> {
+        (0..81)
+            .map(|i| (i / 9, i % 9))
+            .filter(|&(row, col)| {
+                let num = self.board[row][col];
+                num > 0 && peers(row, col).any(|(r, c)| self.board[r][c] == num)
+            })
+            .collect()
+    }
+    /// Filled cells that are not givens and disagree with the solution, empty without a
+    /// solution
+    pub fn wrong_entries(&self) -> Vec<(usize, usize)> {
+        let Some(solution) = self.solution else {
+            return vec![];
+        };
+        (0..81)
+            .map(|i| (i / 9, i % 9))
+            .filter(|&(row, col)| {
+                let num = self.board[row][col];
+                let given = self.puzzle.is_some_and(|puzzle| puzzle[row][col] != 0);
+                num > 0 && !given && num != solution[row][col]
+            })
+            .collect()
+    }
+    /// Whether no row, column or box holds a digit twice. Empty cells are allowed.
+    pub fn is_valid(&self) -> bool {
+        self.conflicts().is_empty()
+    }
+    /// Whether every cell is filled and every house holds 1 to 9 exactly once
+    pub fn is_complete(&self) -> bool {
+        self.board
+            .iter()
+            .flatten()
+            .all(|&num| (1..=9).contains(&num))
+            && self.is_valid()
+    }
+    /// Compares the board against the solution, or checks it against the rules when no
+    /// solution is stored
     pub fn won(&self) -> bool {
-        if let Some(solution) = self.solution {
-            return solution == self.board;
+        match self.solution {
+            Some(solution) => solution == self.board,
+            None => self.is_complete(),
         }
-        false
     }
 }
 
+/// Bitmask for a single digit, 0 (an empty cell) maps to an empty mask
+pub fn bit(num: usize) -> u16 {
+    if num == 0 { 0 } else { 1 << (num - 1) }
+}
+
+/// Digits set in a candidate bitmask, in ascending order
+pub fn digits(mask: u16) -> impl Iterator<Item = usize> {
+    (1..=9).filter(move |&num| mask & bit(num) != 0)
+}
+
+/// Index of the box a cell sits in
+pub fn box_of(row: usize, col: usize) -> usize {
+    row / 3 * 3 + col / 3
+}
+
+/// Every cell that shares a row, column or box with the given cell, excluding itself
+pub fn peers(row: usize, col: usize) -> impl Iterator<Item = (usize, usize)> {
+    (0..81).map(|i| (i / 9, i % 9)).filter(move |&(r, c)| {
+        (r, c) != (row, col) && (r == row || c == col || box_of(r, c) == box_of(row, col))
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    fn solved() -> [[usize; 9]; 9] {
+        let digits: Vec<usize> =
+            "483921657967345821251876493548132976729564138136798245372689514814253769695417382"
+                .bytes()
+                .map(|b| (b - b'0') as usize)
+                .collect();
+        std::array::from_fn(|r| std::array::from_fn(|c| digits[r * 9 + c]))
+    }
+
     #[test]
     fn test_set_get_board() {
         let mut sudoku = Sudoku::new();
@@ -99,6 +365,7 @@ mod tests {
         let solution = [[1, 2, 3, 4, 5, 6, 7, 8, 9]; 9];
         sudoku.set_solution(solution);
         assert_eq!(sudoku.get_solution(), Some(solution));
+        assert!(sudoku.has_unique_solution());
     }
     #[test]
     fn test_set_get_difficulty() {
@@ -108,6 +375,21 @@ mod tests {
         assert_eq!(sudoku.get_difficulty(), Some(difficulty));
     }
     #[test]
+    fn test_set_get_rating() {
+        let mut sudoku = Sudoku::new();
+        assert_eq!(sudoku.get_rating(), None);
+        sudoku.set_rating(Some(4.5));
+        assert_eq!(sudoku.get_rating(), Some(4.5));
+        sudoku.clear_board();
+        assert_eq!(sudoku.get_rating(), None);
+    }
+    #[test]
+    fn test_difficulty_names() {
+        assert_eq!(Difficulty::all().len(), 7);
+        assert_eq!(Difficulty::Beginner.to_string(), "Beginner");
+        assert_eq!(Difficulty::Diabolical.to_string(), "Diabolical");
+    }
+    #[test]
     fn test_clear_board() {
         let mut sudoku = Sudoku::new();
         let board = [[1; 9]; 9];
@@ -120,6 +402,26 @@ mod tests {
         assert_eq!(sudoku.get_board(), [[0; 9]; 9]);
         assert_eq!(sudoku.get_puzzle(), None);
         assert_eq!(sudoku.get_solution(), None);
+        assert!(!sudoku.has_unique_solution());
+    }
+    #[test]
+    fn test_uniqueness() {
+        let mut sudoku = Sudoku::new();
+        assert_eq!(sudoku.uniqueness(), Uniqueness::Multiple);
+        assert_eq!(sudoku.count_solutions(3), 3);
+        sudoku.update_cell(0, 0, 5);
+        sudoku.update_cell(0, 1, 5);
+        assert_eq!(sudoku.uniqueness(), Uniqueness::NoSolution);
+        assert_eq!(sudoku.count_solutions(3), 0);
+        let board: Vec<usize> =
+            "003020600900305001001806400008102900700000008006708200002609500800203009005010300"
+                .bytes()
+                .map(|b| (b - b'0') as usize)
+                .collect();
+        sudoku.set_board(std::array::from_fn(|r| {
+            std::array::from_fn(|c| board[r * 9 + c])
+        }));
+        assert_eq!(sudoku.uniqueness(), Uniqueness::Unique);
     }
     #[test]
     fn test_update_cell() {
@@ -167,4 +469,152 @@ mod tests {
         sudoku.set_solution(solution);
         assert_eq!(sudoku.won(), true);
     }
+    #[test]
+    fn test_won_without_solution() {
+        let mut sudoku = Sudoku::new();
+        assert!(!sudoku.won());
+        sudoku.set_board(solved());
+        assert!(sudoku.won());
+        sudoku.update_cell(8, 8, 0);
+        assert!(!sudoku.won());
+    }
+    #[test]
+    fn test_conflicts() {
+        let mut sudoku = Sudoku::new();
+        assert!(sudoku.conflicts().is_empty());
+        sudoku.update_cell(0, 0, 3);
+        sudoku.update_cell(0, 5, 3);
+        sudoku.update_cell(2, 2, 3);
+        sudoku.update_cell(4, 4, 3);
+        sudoku.update_cell(8, 8, 7);
+        assert_eq!(sudoku.conflicts(), vec![(0, 0), (0, 5), (2, 2)]);
+        sudoku.update_cell(0, 0, 0);
+        assert!(sudoku.conflicts().is_empty());
+    }
+    #[test]
+    fn test_wrong_entries() {
+        let mut sudoku = Sudoku::new();
+        sudoku.update_cell(0, 0, 1);
+        assert!(sudoku.wrong_entries().is_empty());
+        let mut puzzle = solved();
+        puzzle[0] = [0; 9];
+        puzzle[1] = [0; 9];
+        sudoku.set_board(puzzle);
+        sudoku.set_puzzle(puzzle);
+        sudoku.set_solution(solved());
+        // 9 breaks no rule in r1c1 yet belongs in r2c1
+        sudoku.update_cell(0, 0, 9);
+        sudoku.update_cell(0, 1, 8);
+        assert!(sudoku.conflicts().is_empty());
+        assert_eq!(sudoku.wrong_entries(), vec![(0, 0)]);
+    }
+    #[test]
+    fn test_is_valid() {
+        let mut sudoku = Sudoku::new();
+        assert!(sudoku.is_valid());
+        assert!(!sudoku.is_complete());
+        sudoku.set_board(solved());
+        assert!(sudoku.is_valid());
+        assert!(sudoku.is_complete());
+        // Swapping two digits of a row keeps the row but breaks both columns
+        sudoku.update_cell(0, 0, 8);
+        sudoku.update_cell(0, 1, 4);
+        assert!(!sudoku.is_valid());
+        assert!(!sudoku.is_complete());
+        let mut sudoku = Sudoku::new();
+        sudoku.update_cell(0, 0, 3);
+        sudoku.update_cell(2, 2, 3);
+        assert!(!sudoku.is_valid());
+    }
+    #[test]
+    fn test_house_cells() {
+        assert_eq!(House::Row(2).cells()[4], (2, 4));
+        assert_eq!(House::Column(5).cells()[8], (8, 5));
+        assert_eq!(
+            House::Box(4).cells(),
+            [
+                (3, 3),
+                (3, 4),
+                (3, 5),
+                (4, 3),
+                (4, 4),
+                (4, 5),
+                (5, 3),
+                (5, 4),
+                (5, 5)
+            ]
+        );
+        assert_eq!(House::all().count(), 27);
+        assert!(House::Box(8).contains((7, 7)));
+        assert_eq!(House::Box(1).to_string(), "box 2");
+    }
+    #[test]
+    fn test_new_candidates() {
+        let sudoku = Sudoku::new();
+        assert_eq!(sudoku.get_candidates(), [[ALL_CANDIDATES; 9]; 9]);
+    }
+    #[test]
+    fn test_compute_candidates() {
+        let mut sudoku = Sudoku::new();
+        let mut board = [[0; 9]; 9];
+        board[0][0] = 1;
+        board[0][8] = 2;
+        board[8][1] = 3;
+        board[2][2] = 4;
+        sudoku.set_board(board);
+        assert_eq!(sudoku.get_cell_candidates(0, 0), 0);
+        assert_eq!(
+            sudoku.get_cell_candidates(0, 1),
+            ALL_CANDIDATES & !(bit(1) | bit(2) | bit(3) | bit(4))
+        );
+        assert_eq!(sudoku.get_cell_candidates(4, 4), ALL_CANDIDATES);
+        assert!(!sudoku.has_candidate(5, 0, 1));
+        assert!(sudoku.has_candidate(5, 0, 2));
+    }
+    #[test]
+    fn test_eliminate_restore_candidate() {
+        let mut sudoku = Sudoku::new();
+        assert!(sudoku.eliminate_candidate(4, 4, 5));
+        assert!(!sudoku.eliminate_candidate(4, 4, 5));
+        assert!(!sudoku.has_candidate(4, 4, 5));
+        assert!(sudoku.restore_candidate(4, 4, 5));
+        assert!(sudoku.has_candidate(4, 4, 5));
+        sudoku.update_cell(4, 0, 5);
+        sudoku.eliminate_candidate(4, 4, 5);
+        // 5 is already placed in row 4 so the rules forbid restoring it
+        assert!(!sudoku.restore_candidate(4, 4, 5));
+    }
+    #[test]
+    fn test_update_cell_candidates() {
+        let mut sudoku = Sudoku::new();
+        sudoku.update_cell(0, 0, 7);
+        assert_eq!(sudoku.get_cell_candidates(0, 0), 0);
+        assert!(!sudoku.has_candidate(0, 8, 7));
+        assert!(!sudoku.has_candidate(8, 0, 7));
+        assert!(!sudoku.has_candidate(2, 2, 7));
+        assert!(sudoku.has_candidate(3, 3, 7));
+        sudoku.update_cell(0, 0, 3);
+        assert!(sudoku.has_candidate(0, 8, 7));
+        assert!(!sudoku.has_candidate(0, 8, 3));
+        sudoku.update_cell(0, 0, 0);
+        assert_eq!(sudoku.get_cell_candidates(0, 0), ALL_CANDIDATES);
+        assert!(sudoku.has_candidate(0, 8, 3));
+    }
+    #[test]
+    fn test_update_cell_keeps_eliminations() {
+        let mut sudoku = Sudoku::new();
+        sudoku.eliminate_candidate(0, 5, 2);
+        sudoku.update_cell(8, 8, 4);
+        assert!(!sudoku.has_candidate(0, 5, 2));
+    }
+    #[test]
+    fn test_update_cell_restore_respects_other_peers() {
+        let mut sudoku = Sudoku::new();
+        sudoku.update_cell(0, 0, 7);
+        sudoku.update_cell(1, 4, 7);
+        sudoku.update_cell(0, 0, 0);
+        // Row 1 still holds a 7 so the box and row peers cannot get it back
+        assert!(!sudoku.has_candidate(1, 0, 7));
+        assert!(sudoku.has_candidate(0, 8, 7));
+    }
 }
@@ -4,6 +4,7 @@ use iced::{
 };
 
 use crate::app::Message;
+use crate::sudoku::bit;
 
 pub fn solution_ui(board: Option<[[usize; 9]; 9]>, size: f32) -> Element<'static, Message> {
     let mut ui = column![];
@@ -25,6 +26,30 @@ pub fn solution_ui(board: Option<[[usize; 9]; 9]>, size: f32) -> Element<'static
     ui.into()
 }
 
-pub fn strategy_ui() -> Element<'static, Message> {
-    row![checkbox("label", true),].into()
+/// Pencil marks of a cell laid out like the number pad, with blanks for missing digits
+pub fn candidates_text(candidates: u16) -> String {
+    (0..3)
+        .map(|r| {
+            (1..=3)
+                .map(|c| {
+                    let num = r * 3 + c;
+                    if candidates & bit(num) != 0 {
+                        num.to_string()
+                    } else {
+                        " ".to_string()
+                    }
+                })
+                .collect::<Vec<_>>()
+                .join(" ")
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+pub fn strategy_ui(index: usize, name: &str, enabled: bool) -> Element<'static, Message> {
+    row![
+        checkbox(name.to_string(), enabled)
+            .on_toggle(move |value| Message::ToggleStrategy(index, value)),
+    ]
+    .into()
 }